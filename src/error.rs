@@ -0,0 +1,439 @@
+// src/error.rs
+use cosmwasm_std::StdError;
+use thiserror::Error;
+use crate::state::{Role, PauseScope};
+
+/// Errors returned by the instantiate, execute and migrate entry points.
+///
+/// Every message starts with a stable `E<code>` prefix so clients can match on the code
+/// instead of the wording. Codes are grouped by area and must never be reused or renumbered.
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("E{}: {}", self.code(), .0)]
+    AllocationConfig(#[from] AllocationConfigError),
+
+    // Authorization (1xx)
+    #[error("E{}: Unauthorized: requires the {role:?} role", self.code())]
+    MissingRole { role: Role },
+
+    #[error("E{}: Unauthorized: Only the contract manager can grant or revoke Admin", self.code())]
+    AdminRoleManagerOnly,
+
+    #[error("E{}: Address does not hold this role", self.code())]
+    RoleNotHeld,
+
+    #[error("E{}: Unauthorized: Only the allocation manager or an allocation admin can edit this allocation", self.code())]
+    NotAllocationManager,
+
+    #[error("E{}: Unauthorized: Only the claimer can claim this allocation", self.code())]
+    NotAllocationClaimer,
+
+    #[error("E{}: Unauthorized: Only the proposed {role} can accept", self.code())]
+    NotProposedAddress { role: &'static str },
+
+    #[error("E{}: Invalid token sender", self.code())]
+    InvalidTokenSender,
+
+    #[error("E{}: Unauthorized: Only the contract manager can do this", self.code())]
+    ManagerOnly,
+
+    // Registration (2xx)
+    #[error("E{}: User not registered", self.code())]
+    NotRegistered,
+
+    #[error("E{}: Registration has expired", self.code())]
+    RegistrationExpired,
+
+    #[error("E{}: Registration still valid, cannot re-register yet", self.code())]
+    RegistrationStillValid,
+
+    #[error("E{}: ID hash already registered and not expired", self.code())]
+    IdHashRegistered,
+
+    #[error("E{}: One day hasn't passed since the last claim", self.code())]
+    ClaimTooSoon,
+
+    #[error("E{}: Delegate not registered", self.code())]
+    DelegateNotRegistered,
+
+    #[error("E{}: Delegate registration has expired", self.code())]
+    DelegateRegistrationExpired,
+
+    // Voting and delegation (3xx)
+    #[error("E{}: Allocations are delegated; undelegate first", self.code())]
+    AllocationsDelegated,
+
+    #[error("E{}: Allocations are not delegated", self.code())]
+    NotDelegated,
+
+    #[error("E{}: Voting weight is zero", self.code())]
+    ZeroVotingWeight,
+
+    #[error("E{}: Duplicate allocation ID found", self.code())]
+    DuplicateAllocation,
+
+    #[error("E{}: Percentage error: allocations must sum to {total}", self.code())]
+    InvalidPercentageTotal { total: &'static str },
+
+    #[error("E{}: Cannot delegate to yourself", self.code())]
+    SelfDelegation,
+
+    #[error("E{}: Delegation cycle detected", self.code())]
+    DelegationCycle,
+
+    #[error("E{}: Delegation chain too long", self.code())]
+    DelegationChainTooLong,
+
+    #[error("E{}: Cannot change voting rules of a past epoch", self.code())]
+    PastEpochVotingRules,
+
+    #[error("E{}: Cannot change voting rules once the epoch has votes", self.code())]
+    EpochHasVotes,
+
+    #[error("E{}: Auto-renew is not enabled", self.code())]
+    AutoRenewNotEnabled,
+
+    #[error("E{}: Cannot skip a past epoch", self.code())]
+    SkipPastEpoch,
+
+    // Allocations (4xx)
+    #[error("E{}: Allocation not found", self.code())]
+    AllocationNotFound,
+
+    #[error("E{}: Use ProposeAllocationManager to change the allocation manager", self.code())]
+    ManagerChangeNeedsProposal,
+
+    #[error("E{}: Use ProposeAllocationClaimer to change the allocation claimer", self.code())]
+    ClaimerChangeNeedsProposal,
+
+    #[error("E{}: No pending manager for this allocation", self.code())]
+    NoPendingAllocationManager,
+
+    #[error("E{}: No pending claimer for this allocation", self.code())]
+    NoPendingAllocationClaimer,
+
+    #[error("E{}: Missing recipient code hash for allocation", self.code())]
+    MissingRecipientCodeHash,
+
+    #[error("E{}: An allocation can have at most {max} recipients", self.code())]
+    TooManyRecipients { max: usize },
+
+    #[error("E{}: Recipient weight must be greater than zero", self.code())]
+    ZeroRecipientWeight,
+
+    #[error("E{}: Nothing vesting for this allocation", self.code())]
+    NothingVesting,
+
+    // Proposals (5xx)
+    #[error("E{}: Allocation proposals are not enabled", self.code())]
+    ProposalsDisabled,
+
+    #[error("E{}: Deposit must be at least {min}", self.code())]
+    DepositTooLow { min: String },
+
+    #[error("E{}: Proposal not found", self.code())]
+    ProposalNotFound,
+
+    #[error("E{}: Proposal has already been decided", self.code())]
+    ProposalDecided,
+
+    #[error("E{}: Proposal already endorsed", self.code())]
+    AlreadyEndorsed,
+
+    #[error("E{}: Proposal deposit must be greater than zero", self.code())]
+    ZeroProposalDeposit,
+
+    #[error("E{}: Endorsement threshold must be 0 or at least {min}", self.code())]
+    EndorsementThresholdTooLow { min: u32 },
+
+    // Reward tokens and voter rewards (6xx)
+    #[error("E{}: Reward token already registered", self.code())]
+    RewardTokenRegistered,
+
+    #[error("E{}: At most {max} reward tokens can be registered", self.code())]
+    TooManyRewardTokens { max: usize },
+
+    #[error("E{}: Stream duration must be at least {min} seconds", self.code())]
+    StreamTooShort { min: u64 },
+
+    #[error("E{}: Token is not a registered reward token", self.code())]
+    UnknownRewardToken,
+
+    #[error("E{}: Voter reward share cannot exceed {max} basis points", self.code())]
+    VoterRewardShareTooHigh { max: u16 },
+
+    // Configuration and admin (7xx)
+    #[error("E{}: Admin timelock is enabled: queue this action with QueueAdminAction", self.code())]
+    Timelocked,
+
+    #[error("E{}: Admin timelock cannot exceed {max} seconds", self.code())]
+    TimelockTooLong { max: u64 },
+
+    #[error("E{}: Admin timelock is not enabled", self.code())]
+    TimelockDisabled,
+
+    #[error("E{}: Admin action not found", self.code())]
+    AdminActionNotFound,
+
+    #[error("E{}: Admin action can't be executed before {eta}", self.code())]
+    AdminActionNotReady { eta: u64 },
+
+    #[error("E{}: registration_validity_seconds must be between {min} and {max}", self.code())]
+    InvalidRegistrationValidity { min: u64, max: u64 },
+
+    #[error("E{}: registry_hash must be 64 hex characters", self.code())]
+    InvalidRegistryHash,
+
+    #[error("E{}: No pending manager proposal", self.code())]
+    NoPendingManager,
+
+    #[error("E{}: Manager proposal has expired", self.code())]
+    ManagerProposalExpired,
+
+    #[error("E{}: {name} is not a contract resolved through the registry", self.code())]
+    UnknownRegistryName { name: String },
+
+    #[error("E{}: Pinned code hash must be 64 hex characters", self.code())]
+    InvalidPinnedCodeHash,
+
+    #[error("E{}: {name} is not pinned", self.code())]
+    RegistryNameNotPinned { name: String },
+
+    // Pausing (8xx)
+    #[error("E{}: Contract is paused: {scope:?}", self.code())]
+    Paused { scope: PauseScope },
+
+    #[error("E{}: {scope:?} is already {}", self.code(), if *.paused { "paused" } else { "unpaused" })]
+    PauseUnchanged { scope: PauseScope, paused: bool },
+
+    // Arithmetic (9xx)
+    #[error("E{}: Overflow in {0}", self.code())]
+    Overflow(&'static str),
+
+    #[error("E{}: Underflow in {0}", self.code())]
+    Underflow(&'static str),
+
+    // Migration (10xx)
+    #[error("E{}: Cannot migrate a {stored} contract with {current} code", self.code())]
+    WrongContract { stored: String, current: String },
+
+    #[error("E{}: Invalid contract version: {version}", self.code())]
+    InvalidVersion { version: String },
+
+    #[error("E{}: Cannot downgrade from {stored} to {current}", self.code())]
+    MigrationDowngrade { stored: String, current: String },
+
+    #[error("E{}: Migrating from {version} requires the Migrate message with registry details", self.code())]
+    MigrationNeedsRegistry { version: String },
+
+    #[error("E{}: Registry details only apply when migrating a legacy deployment; use Upgrade", self.code())]
+    UnexpectedRegistryDetails,
+
+    #[error("E{}: Dry run, nothing was migrated: {preview}", self.code())]
+    MigrationDryRun { preview: String },
+}
+
+impl ContractError {
+    /// Stable numeric code for the error; 0 for errors bubbled up from the standard library
+    pub fn code(&self) -> u16 {
+        match self {
+            ContractError::Std(_) => 0,
+            ContractError::AllocationConfig(err) => err.code(),
+
+            ContractError::MissingRole { .. } => 100,
+            ContractError::AdminRoleManagerOnly => 101,
+            ContractError::RoleNotHeld => 102,
+            ContractError::NotAllocationManager => 103,
+            ContractError::NotAllocationClaimer => 104,
+            ContractError::NotProposedAddress { .. } => 105,
+            ContractError::InvalidTokenSender => 106,
+            ContractError::ManagerOnly => 107,
+
+            ContractError::NotRegistered => 200,
+            ContractError::RegistrationExpired => 201,
+            ContractError::RegistrationStillValid => 202,
+            ContractError::IdHashRegistered => 203,
+            ContractError::ClaimTooSoon => 204,
+            ContractError::DelegateNotRegistered => 205,
+            ContractError::DelegateRegistrationExpired => 206,
+
+            ContractError::AllocationsDelegated => 300,
+            ContractError::NotDelegated => 301,
+            ContractError::ZeroVotingWeight => 302,
+            ContractError::DuplicateAllocation => 303,
+            ContractError::InvalidPercentageTotal { .. } => 304,
+            ContractError::SelfDelegation => 305,
+            ContractError::DelegationCycle => 306,
+            ContractError::DelegationChainTooLong => 307,
+            ContractError::PastEpochVotingRules => 308,
+            ContractError::EpochHasVotes => 309,
+            ContractError::AutoRenewNotEnabled => 310,
+            ContractError::SkipPastEpoch => 311,
+
+            ContractError::AllocationNotFound => 400,
+            ContractError::ManagerChangeNeedsProposal => 401,
+            ContractError::ClaimerChangeNeedsProposal => 402,
+            ContractError::NoPendingAllocationManager => 403,
+            ContractError::NoPendingAllocationClaimer => 404,
+            ContractError::MissingRecipientCodeHash => 405,
+            ContractError::TooManyRecipients { .. } => 406,
+            ContractError::ZeroRecipientWeight => 407,
+            ContractError::NothingVesting => 408,
+
+            ContractError::ProposalsDisabled => 500,
+            ContractError::DepositTooLow { .. } => 501,
+            ContractError::ProposalNotFound => 502,
+            ContractError::ProposalDecided => 503,
+            ContractError::AlreadyEndorsed => 504,
+            ContractError::ZeroProposalDeposit => 505,
+            ContractError::EndorsementThresholdTooLow { .. } => 506,
+
+            ContractError::RewardTokenRegistered => 600,
+            ContractError::TooManyRewardTokens { .. } => 601,
+            ContractError::StreamTooShort { .. } => 602,
+            ContractError::UnknownRewardToken => 603,
+            ContractError::VoterRewardShareTooHigh { .. } => 604,
+
+            ContractError::Timelocked => 700,
+            ContractError::TimelockTooLong { .. } => 701,
+            ContractError::TimelockDisabled => 702,
+            ContractError::AdminActionNotFound => 703,
+            ContractError::AdminActionNotReady { .. } => 704,
+            ContractError::InvalidRegistrationValidity { .. } => 705,
+            ContractError::InvalidRegistryHash => 706,
+            ContractError::NoPendingManager => 707,
+            ContractError::ManagerProposalExpired => 708,
+            ContractError::UnknownRegistryName { .. } => 709,
+            ContractError::InvalidPinnedCodeHash => 710,
+            ContractError::RegistryNameNotPinned { .. } => 711,
+
+            ContractError::Paused { .. } => 800,
+            ContractError::PauseUnchanged { .. } => 801,
+
+            ContractError::Overflow(_) => 900,
+            ContractError::Underflow(_) => 901,
+
+            ContractError::WrongContract { .. } => 1000,
+            ContractError::InvalidVersion { .. } => 1001,
+            ContractError::MigrationDowngrade { .. } => 1002,
+            ContractError::MigrationNeedsRegistry { .. } => 1003,
+            ContractError::UnexpectedRegistryDetails => 1004,
+            ContractError::MigrationDryRun { .. } => 1005,
+        }
+    }
+}
+
+/// Reasons an allocation's payout configuration is rejected when it is written
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AllocationConfigError {
+    #[error("Description exceeds max length of {max}")]
+    DescriptionTooLong { max: usize },
+
+    #[error("Invalid {field} address: {address}")]
+    InvalidAddress { field: &'static str, address: String },
+
+    #[error("Missing code hash for {address}: use_send requires the receiving contract's code hash")]
+    MissingCodeHash { address: String },
+
+    #[error("Invalid code hash for {address}: expected 64 hex characters")]
+    InvalidCodeHash { address: String },
+
+    #[error("{address} is not a contract and cannot receive with use_send")]
+    NotAContract { address: String },
+}
+
+impl AllocationConfigError {
+    pub fn code(&self) -> u16 {
+        match self {
+            AllocationConfigError::DescriptionTooLong { .. } => 410,
+            AllocationConfigError::InvalidAddress { .. } => 411,
+            AllocationConfigError::MissingCodeHash { .. } => 412,
+            AllocationConfigError::InvalidCodeHash { .. } => 413,
+            AllocationConfigError::NotAContract { .. } => 414,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Uint128;
+    use crate::msg::ExecuteMsg;
+    use crate::state::AllocationPercentage;
+    use crate::testing::{setup, exec, register, MANAGER};
+
+    const VALIDITY_SECONDS: u64 = 60 * 60 * 24 * 30;
+
+    fn vote(percentage: u128) -> ExecuteMsg {
+        ExecuteMsg::SetAllocation {
+            percentages: vec![AllocationPercentage { allocation_id: 1, percentage: Uint128::new(percentage) }],
+        }
+    }
+
+    fn assert_error(err: ContractError, expected: ContractError, code: u16) {
+        assert_eq!(err, expected);
+        assert_eq!(err.code(), code);
+        assert!(err.to_string().starts_with(&format!("E{}: ", code)), "{}", err);
+    }
+
+    #[test]
+    fn unauthorized() {
+        let mut deps = setup();
+        let err = exec(&mut deps, mock_env(), "stranger", ExecuteMsg::SetPause {
+            scope: PauseScope::AllocationVoting,
+            paused: true,
+        }).unwrap_err();
+        assert_error(err, ContractError::MissingRole { role: Role::Pauser }, 100);
+    }
+
+    #[test]
+    fn expired_registration() {
+        let mut deps = setup();
+        register(&mut deps, mock_env(), "voter");
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(VALIDITY_SECONDS + 1);
+        let err = exec(&mut deps, env, "voter", vote(100)).unwrap_err();
+        assert_error(err, ContractError::RegistrationExpired, 201);
+    }
+
+    #[test]
+    fn bad_percentage_total() {
+        let mut deps = setup();
+        register(&mut deps, mock_env(), "voter");
+
+        let err = exec(&mut deps, mock_env(), "voter", vote(60)).unwrap_err();
+        assert_error(err, ContractError::InvalidPercentageTotal { total: "100%" }, 304);
+    }
+
+    #[test]
+    fn paused() {
+        let mut deps = setup();
+        register(&mut deps, mock_env(), "voter");
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetPause {
+            scope: PauseScope::AllocationVoting,
+            paused: true,
+        }).unwrap();
+
+        let err = exec(&mut deps, mock_env(), "voter", vote(100)).unwrap_err();
+        assert_error(err, ContractError::Paused { scope: PauseScope::AllocationVoting }, 800);
+    }
+
+    #[test]
+    fn timelocked() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetAdminTimelock {
+            delay_seconds: 3_600,
+        }).unwrap();
+
+        let err = exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::GrantRole {
+            address: "keeper".to_string(),
+            role: Role::Keeper,
+        }).unwrap_err();
+        assert_error(err, ContractError::Timelocked, 700);
+    }
+}
@@ -1,30 +1,247 @@
 // src/execute/allocation.rs
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, Addr, to_binary, CosmosMsg, WasmMsg, Timestamp};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Addr, Timestamp,
+    Storage};
 use crate::state::{ALLOCATION_OPTIONS, ALLOCATION_IDS, USER_ALLOCATIONS, State, CONFIG, Allocation, AllocationConfig, AllocationPercentage,
-    AllocationState, STATE, REGISTRATIONS, UserAllocations, MAX_DESCRIPTION_LENGTH, query_registry};
-use crate::msg::SendMsg;
-use secret_toolkit::snip20::{HandleMsg};
-
-const INDEX_PRECISION: u128 = 1_000_000_000_000;
-const REWARD_RATE: u128 = 1_000_000; // 1 ERTH per second (6 decimal places)
-
-pub fn update_reward_index(state: &mut State, current_time: Timestamp) {
+    AllocationState, STATE, REGISTRATIONS, UserAllocations, MAX_DESCRIPTION_LENGTH, UserVote, USER_VOTES,
+    FLAT_VOTE_WEIGHT, TallyMode, PercentageScale, EpochVotingRules, EPOCH_VOTING_RULES, DELEGATIONS,
+    AllocationEpochStats, ALLOCATION_EPOCH_STATS, EpochAllocationResult, EpochResults, EPOCH_RESULTS,
+    ALLOCATION_VESTING_DURATION, ALLOCATION_BONUS_POOL, PendingReceiveChange, PENDING_RECEIVE_CHANGES,
+    PAYOUT_CHANGE_DELAY_SECONDS};
+use crate::execute::voting::voting_weight;
+use crate::execute::vote_expiry::{process_vote_expiries, enqueue_vote_expiry, LAZY_EXPIRY_LIMIT};
+use crate::execute::delegation::{delegated_weight, shift_delegated_weight};
+use crate::execute::recipients::{allocation_recipients, mint_to_recipients, mint_msg,
+    transfer_to_recipients};
+use crate::execute::vesting::lock_vesting;
+use crate::execute::allocation_changes::{apply_pending_payout, check_allocation_manager};
+use crate::execute::validation::validate_allocation_config;
+use crate::execute::reward_stream::{update_stream_indexes, settle_token_rewards};
+use crate::execute::voter_reward::{voter_reward_bps, update_voter_index, set_voter_weight, close_voter_epoch};
+use crate::execute::registry::resolve_contracts;
+use crate::error::{ContractError, AllocationConfigError};
+
+pub const INDEX_PRECISION: u128 = 1_000_000_000_000;
+pub const REWARD_RATE: u128 = 1_000_000; // 1 ERTH per second (6 decimal places)
+const QUADRATIC_SCALE: u128 = 1_000_000; // keeps square roots of small credit counts precise
+
+/// Advance the allocation index by the emission left after the voter share (`voter_reward_bps`)
+pub fn update_reward_index(state: &mut State, current_time: Timestamp, voter_reward_bps: u16) {
     let time_elapsed = current_time.seconds().saturating_sub(state.last_upkeep.seconds());
     if time_elapsed > 0 && !state.total_allocations.is_zero() {
-        let new_rewards = Uint128::from(time_elapsed) * Uint128::from(REWARD_RATE);
+        let new_rewards = (Uint128::from(time_elapsed) * Uint128::from(REWARD_RATE))
+            .multiply_ratio(10_000u128 - voter_reward_bps as u128, 10_000u128);
         state.reward_index = state.reward_index +
             (new_rewards * Uint128::from(INDEX_PRECISION) / state.total_allocations);
     }
     state.last_upkeep = current_time;
 }
 
-fn settle_allocation(allocation_state: &mut AllocationState, reward_index: Uint128) {
+/// Bring the ERTH allocation and voter indexes and every partner token stream up to `current_time`
+pub fn update_reward_indexes(storage: &mut dyn Storage, state: &mut State, current_time: Timestamp) -> StdResult<()> {
+    update_voter_index(storage, state.last_upkeep, current_time)?;
+    update_reward_index(state, current_time, voter_reward_bps(storage));
+    update_stream_indexes(storage, state.total_allocations, current_time)
+}
+
+/// Drop votes whose registration lapsed, then bring the reward indexes up to `current_time`
+pub fn sync_allocations(storage: &mut dyn Storage, state: &mut State, current_time: Timestamp) -> Result<(), ContractError> {
+    process_vote_expiries(storage, state, current_time, LAZY_EXPIRY_LIMIT)?;
+    Ok(update_reward_indexes(storage, state, current_time)?)
+}
+
+/// Credit an allocation with rewards accrued since its last settlement, returning the amount credited
+pub fn settle_allocation(allocation_state: &mut AllocationState, reward_index: Uint128) -> Uint128 {
+    let mut pending = Uint128::zero();
     if !allocation_state.amount_allocated.is_zero() {
         let delta = reward_index - allocation_state.last_reward_index;
-        let pending = allocation_state.amount_allocated * delta / Uint128::from(INDEX_PRECISION);
+        pending = allocation_state.amount_allocated * delta / Uint128::from(INDEX_PRECISION);
         allocation_state.accumulated_rewards = allocation_state.accumulated_rewards + pending;
     }
     allocation_state.last_reward_index = reward_index;
+    pending
+}
+
+/// Current-epoch tallies of an allocation (zeroed once the epoch has moved on)
+pub fn allocation_epoch_stats(storage: &dyn Storage, allocation_id: u32, epoch: u32) -> AllocationEpochStats {
+    ALLOCATION_EPOCH_STATS.get(storage, &allocation_id)
+        .filter(|stats| stats.epoch == epoch)
+        .unwrap_or(AllocationEpochStats { epoch, ..Default::default() })
+}
+
+/// Settle an allocation and record the rewards it earned in the current epoch's tallies
+fn settle_and_track(
+    storage: &mut dyn Storage,
+    state: &State,
+    allocation: &mut Allocation,
+    voters_added: u32,
+    voters_removed: u32,
+) -> StdResult<()> {
+    let earned = settle_allocation(&mut allocation.state, state.reward_index);
+    settle_token_rewards(storage, allocation.state.allocation_id, allocation.state.amount_allocated)?;
+    if earned.is_zero() && voters_added == 0 && voters_removed == 0 {
+        return Ok(());
+    }
+    let mut stats = allocation_epoch_stats(storage, allocation.state.allocation_id, state.epoch);
+    stats.rewards_earned += earned;
+    // Votes cast before tallies existed aren't counted, so removals saturate
+    stats.voters = (stats.voters + voters_added).saturating_sub(voters_removed);
+    ALLOCATION_EPOCH_STATS.insert(storage, &allocation.state.allocation_id, &stats)
+}
+
+/// Voting rules in force for an epoch
+pub fn epoch_voting_rules(storage: &dyn Storage, epoch: u32) -> EpochVotingRules {
+    EPOCH_VOTING_RULES.get(storage, &epoch).unwrap_or_default()
+}
+
+/// Integer square root (floor) using Newton's method
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value / 2 + 1;
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Amount a single allocation share adds to `amount_allocated` under the epoch's rules.
+/// Credits are `weight * basis_points / 100` whatever the epoch's scale, so tallies of
+/// percent and basis-point epochs stay comparable.
+fn allocation_amount(rules: &EpochVotingRules, weight: Uint128, percentage: Uint128) -> Uint128 {
+    let basis_points = percentage.multiply_ratio(10_000u128, rules.percentage_scale.total());
+    let credits = weight.multiply_ratio(basis_points, 100u128);
+    match rules.tally_mode {
+        TallyMode::Linear => credits,
+        TallyMode::Quadratic => Uint128::from(isqrt(credits.u128().saturating_mul(QUADRATIC_SCALE))),
+    }
+}
+
+/// Current-epoch allocations of a user together with the weight they were applied with
+pub fn load_user_votes(storage: &dyn Storage, address: &Addr, epoch: u32) -> (Vec<AllocationPercentage>, Uint128) {
+    let user_data = USER_ALLOCATIONS.get(storage, address).unwrap_or_default();
+    if user_data.epoch != epoch {
+        return (vec![], Uint128::zero());
+    }
+    // Votes cast before weighting existed were applied with the flat weight
+    let weight = USER_VOTES.get(storage, address)
+        .filter(|vote| vote.epoch == epoch)
+        .map(|vote| vote.weight)
+        .unwrap_or_else(|| Uint128::from(FLAT_VOTE_WEIGHT));
+    (user_data.allocations, weight)
+}
+
+/// Settle each allocation and subtract the user's weighted shares from it and the total
+pub fn remove_votes(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    allocations: &[AllocationPercentage],
+    weight: Uint128,
+) -> Result<(), ContractError> {
+    let rules = epoch_voting_rules(storage, state.epoch);
+    for pct in allocations {
+        if pct.percentage.is_zero() {
+            continue;
+        }
+        if let Some(mut allocation) = ALLOCATION_OPTIONS.get(storage, &pct.allocation_id) {
+            let amount = allocation_amount(&rules, weight, pct.percentage);
+            settle_and_track(storage, state, &mut allocation, 0, 1)?;
+            allocation.state.amount_allocated = allocation.state.amount_allocated.checked_sub(amount)
+                .map_err(|_| ContractError::Underflow("allocation subtraction"))?;
+            state.total_allocations = state.total_allocations.checked_sub(amount)
+                .map_err(|_| ContractError::Underflow("total allocations"))?;
+            ALLOCATION_OPTIONS.insert(storage, &pct.allocation_id, &allocation)?;
+        }
+    }
+    Ok(())
+}
+
+/// Settle each allocation and add the user's weighted shares to it and the total
+pub fn apply_votes(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    allocations: &[AllocationPercentage],
+    weight: Uint128,
+) -> Result<(), ContractError> {
+    let rules = epoch_voting_rules(storage, state.epoch);
+    for pct in allocations {
+        if pct.percentage.is_zero() {
+            continue;
+        }
+        let mut allocation = ALLOCATION_OPTIONS.get(storage, &pct.allocation_id)
+            .ok_or(ContractError::AllocationNotFound)?;
+        let amount = allocation_amount(&rules, weight, pct.percentage);
+        settle_and_track(storage, state, &mut allocation, 1, 0)?;
+        allocation.state.amount_allocated = allocation.state.amount_allocated.checked_add(amount)
+            .map_err(|_| ContractError::Overflow("allocation addition"))?;
+        state.total_allocations = state.total_allocations.checked_add(amount)
+            .map_err(|_| ContractError::Overflow("total allocations"))?;
+        ALLOCATION_OPTIONS.insert(storage, &pct.allocation_id, &allocation)?;
+    }
+    Ok(())
+}
+
+/// Re-tally a user's current-epoch allocations at a new total weight, keeping their expiry
+pub fn set_vote_weight(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    address: &Addr,
+    weight: Uint128,
+) -> Result<(), ContractError> {
+    let (allocations, old_weight) = load_user_votes(storage, address, state.epoch);
+    if allocations.is_empty() || old_weight == weight {
+        return Ok(());
+    }
+    remove_votes(storage, state, &allocations, old_weight)?;
+    apply_votes(storage, state, &allocations, weight)?;
+    let expires = USER_VOTES.get(storage, address)
+        .filter(|vote| vote.epoch == state.epoch)
+        .map(|vote| vote.expires)
+        .unwrap_or_default();
+    USER_VOTES.insert(storage, address, &UserVote { epoch: state.epoch, weight, expires })?;
+    set_voter_weight(storage, state.epoch, address, weight)?;
+    Ok(())
+}
+
+/// Bring a user's vote (or delegation) in line with their current own weight and
+/// registration expiry; no-op if they neither voted this epoch nor delegate
+pub fn reweight_votes(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    address: &Addr,
+    weight: Uint128,
+    expires: Timestamp,
+) -> Result<(), ContractError> {
+    if let Some(mut delegation) = DELEGATIONS.get(storage, address) {
+        if delegation.weight != weight {
+            shift_delegated_weight(storage, state, &delegation.delegate, delegation.weight, weight)?;
+        }
+        if delegation.expires != expires {
+            enqueue_vote_expiry(storage, address, expires)?;
+        }
+        delegation.weight = weight;
+        delegation.expires = expires;
+        DELEGATIONS.insert(storage, address, &delegation)?;
+        return Ok(());
+    }
+
+    let (allocations, _) = load_user_votes(storage, address, state.epoch);
+    if allocations.is_empty() {
+        return Ok(());
+    }
+    let weight = weight + delegated_weight(storage, address);
+    set_vote_weight(storage, state, address, weight)?;
+
+    let old_expires = USER_VOTES.get(storage, address)
+        .filter(|vote| vote.epoch == state.epoch)
+        .map(|vote| vote.expires);
+    if old_expires != Some(expires) {
+        USER_VOTES.insert(storage, address, &UserVote { epoch: state.epoch, weight, expires })?;
+        enqueue_vote_expiry(storage, address, expires)?;
+    }
+    Ok(())
 }
 
 pub fn set_allocation(
@@ -32,115 +249,114 @@ pub fn set_allocation(
     env: Env,
     info: MessageInfo,
     percentages: Vec<AllocationPercentage>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // Load config to get registration validity period
     let config = CONFIG.load(deps.storage)?;
 
     // Load user registration and check validity
-    if let Some(registration) = REGISTRATIONS.get_by_address(deps.storage, &info.sender)? {
-        let registration_age = env.block.time.seconds() - registration.registration_timestamp.seconds();
-        if registration_age > config.registration_validity_seconds {
-            return Err(StdError::generic_err("Registration has expired"));
-        }
-    } else {
-        return Err(StdError::generic_err("User not registered"));
+    let registration = REGISTRATIONS.get_by_address(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotRegistered)?;
+    let registration_age = env.block.time.seconds() - registration.registration_timestamp.seconds();
+    if registration_age > config.registration_validity_seconds {
+        return Err(ContractError::RegistrationExpired);
     }
+    let expires = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
 
-    let mut state = STATE.load(deps.storage)?;
+    if DELEGATIONS.contains(deps.storage, &info.sender) {
+        return Err(ContractError::AllocationsDelegated);
+    }
 
-    // Update global reward index
-    update_reward_index(&mut state, env.block.time);
+    // Resolve the weight this vote carries under the configured mode, plus anything delegated to the user
+    let own_weight = voting_weight(deps.as_ref(), &env, &config, &registration)?;
+    if own_weight.is_zero() {
+        return Err(ContractError::ZeroVotingWeight);
+    }
+    let weight = own_weight + delegated_weight(deps.storage, &info.sender);
 
-    // Load old user allocations, checking epoch
-    let old_user_data = USER_ALLOCATIONS.get(deps.storage, &info.sender).unwrap_or_default();
-    let old_allocations = if old_user_data.epoch == state.epoch {
-        old_user_data.allocations
-    } else {
-        vec![]
-    };
+    let mut state = STATE.load(deps.storage)?;
 
-    // Subtract old allocations
-    for old_pct in &old_allocations {
-        if let Some(mut allocation) = ALLOCATION_OPTIONS.get(deps.storage, &old_pct.allocation_id) {
-            settle_allocation(&mut allocation.state, state.reward_index);
-            allocation.state.amount_allocated = allocation.state.amount_allocated.checked_sub(old_pct.percentage)
-                .map_err(|_| StdError::generic_err("Underflow in allocation subtraction"))?;
-            state.total_allocations = state.total_allocations.checked_sub(old_pct.percentage)
-                .map_err(|_| StdError::generic_err("Underflow in total allocations"))?;
-            ALLOCATION_OPTIONS.insert(deps.storage, &old_pct.allocation_id, &allocation)?;
-        }
-    }
+    // Drop lapsed votes and update global reward index
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+    // Subtract old allocations for the current epoch
+    let (old_allocations, old_weight) = load_user_votes(deps.storage, &info.sender, state.epoch);
+    remove_votes(deps.storage, &mut state, &old_allocations, old_weight)?;
 
     // Check for duplicate allocation IDs
     let mut seen_ids = std::collections::HashSet::new();
     for pct in &percentages {
         if !seen_ids.insert(pct.allocation_id) {
-            return Err(StdError::generic_err("Duplicate allocation ID found"));
+            return Err(ContractError::DuplicateAllocation);
         }
     }
 
-    // Add new allocations and validate
+    // Ensure that the total percentages add up to 100% in the epoch's scale
     let mut total_percentage = Uint128::zero();
-    for new_pct in &percentages {
-        if new_pct.percentage > Uint128::zero() {
-            let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &new_pct.allocation_id)
-                .ok_or_else(|| StdError::generic_err("Allocation not found"))?;
-            settle_allocation(&mut allocation.state, state.reward_index);
-            allocation.state.amount_allocated = allocation.state.amount_allocated.checked_add(new_pct.percentage)
-                .map_err(|_| StdError::generic_err("Overflow in allocation addition"))?;
-            state.total_allocations = state.total_allocations.checked_add(new_pct.percentage)
-                .map_err(|_| StdError::generic_err("Overflow in total allocations"))?;
-            total_percentage = total_percentage.checked_add(new_pct.percentage)
-                .map_err(|_| StdError::generic_err("Overflow in total percentage"))?;
-            ALLOCATION_OPTIONS.insert(deps.storage, &new_pct.allocation_id, &allocation)?;
-        }
+    for pct in &percentages {
+        total_percentage = total_percentage.checked_add(pct.percentage)
+            .map_err(|_| ContractError::Overflow("total percentage"))?;
     }
-
-    // Ensure that the total percentages add up to 100%
-    if total_percentage != Uint128::from(100u32) {
-        return Err(StdError::generic_err("Percentage error: allocations must sum to 100%"));
+    let scale = epoch_voting_rules(deps.storage, state.epoch).percentage_scale;
+    if total_percentage != scale.total() {
+        return Err(ContractError::InvalidPercentageTotal { total: match scale {
+            PercentageScale::Percent => "100%",
+            PercentageScale::BasisPoints => "10000 basis points",
+        } });
     }
 
-    // Save user allocations with current epoch
+    // Add new allocations
+    apply_votes(deps.storage, &mut state, &percentages, weight)?;
+    set_voter_weight(deps.storage, state.epoch, &info.sender, weight)?;
+
+    // Save user allocations and their weight with current epoch
     USER_ALLOCATIONS.insert(deps.storage, &info.sender, &UserAllocations {
         epoch: state.epoch,
         allocations: percentages,
     })?;
+    USER_VOTES.insert(deps.storage, &info.sender, &UserVote {
+        epoch: state.epoch,
+        weight,
+        expires,
+    })?;
+    enqueue_vote_expiry(deps.storage, &info.sender, expires)?;
 
     STATE.save(deps.storage, &state)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_attribute("action", "set_allocation")
+        .add_attribute("weight", weight.to_string()))
 }
 
 pub fn claim_allocation(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     allocation_id: u32,
-) -> StdResult<Response> {
-    let config = CONFIG.load(deps.storage)?;
+) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
 
-    // Update global reward index
-    update_reward_index(&mut state, env.block.time);
+    // Drop lapsed votes and update global reward index
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
 
     // Load and settle the specific allocation
     let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
-        .ok_or_else(|| StdError::generic_err("Allocation not found"))?;
+        .ok_or(ContractError::AllocationNotFound)?;
 
-    settle_allocation(&mut allocation.state, state.reward_index);
+    settle_and_track(deps.storage, &state, &mut allocation, 0, 0)?;
+    apply_pending_payout(deps.storage, &mut allocation, env.block.time)?;
 
     // If there's a claimer address, check that the info.sender is the claimer
     if let Some(claimer_addr) = &allocation.config.claimer_addr {
         if &info.sender != claimer_addr {
-            return Err(StdError::generic_err("Unauthorized: Only the claimer can claim this allocation"));
+            return Err(ContractError::NotAllocationClaimer);
         }
     }
 
-    // Get the accumulated rewards for this allocation
+    // Get the accumulated rewards for this allocation, plus any sponsored bonus
     let allocation_share = allocation.state.accumulated_rewards;
+    let bonus = ALLOCATION_BONUS_POOL.get(deps.storage, &allocation_id).unwrap_or_default();
 
-    if allocation_share.is_zero() {
+    if allocation_share.is_zero() && bonus.is_zero() {
         ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
         STATE.save(deps.storage, &state)?;
         return Ok(Response::new()
@@ -156,69 +372,27 @@ pub fn claim_allocation(
     allocation.state.last_claim = env.block.time;
 
     // Query registry for erth_token
-    let deps_ref = deps.as_ref();
-    let contracts = query_registry(
-        &deps_ref,
-        &config.registry_contract,
-        &config.registry_hash,
-        vec!["erth_token"],
-    )?;
+    let contracts = resolve_contracts(deps.branch(), env.block.time, &["erth_token"])?;
     let erth_token = &contracts[0];
 
+    // Vesting allocations mint to this contract and release over time through `WithdrawVested`;
+    // otherwise mint each recipient's share, forwarding to `use_send` recipients.
+    // The bonus is already held by this contract, so it is locked or transferred as-is.
+    let vesting_duration = ALLOCATION_VESTING_DURATION.get(deps.storage, &allocation_id);
     let mut messages = Vec::new();
-
-    // Prepare the minting message based on the `use_send` flag
-    if allocation.config.use_send {
-        // Mint to the staking contract and trigger the receive function
-        let mint_msg = HandleMsg::Mint {
-            recipient: env.contract.address.to_string(),
-            amount: allocation_share,
-            padding: None,
-            memo: None,
-        };
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: erth_token.address.to_string(),
-            code_hash: erth_token.code_hash.clone(),
-            msg: to_binary(&mint_msg)?,
-            funds: vec![],
-        }));
-
-        let send_msg = if let Some(receive_hash) = &allocation.config.receive_hash {
-            HandleMsg::Send {
-                recipient: allocation.config.receive_addr.to_string(),
-                recipient_code_hash: Some(receive_hash.clone()),
-                amount: allocation_share,
-                msg: Some(to_binary(&SendMsg::AllocationSend {
-                    allocation_id,
-                })?),
-                memo: None,
-                padding: None,
-            }
-        } else {
-            return Err(StdError::generic_err("Missing recipient code hash for allocation"));
-        };
-
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: erth_token.address.to_string(),
-            code_hash: erth_token.code_hash.clone(),
-            msg: to_binary(&send_msg)?,
-            funds: vec![],
-        }));
+    if let Some(duration) = vesting_duration {
+        lock_vesting(deps.storage, allocation_id, allocation_share + bonus, duration, env.block.time)?;
+        if !allocation_share.is_zero() {
+            messages.push(mint_msg(erth_token, env.contract.address.to_string(), allocation_share)?);
+        }
     } else {
-        // Mint directly to the allocation receiver address
-        let mint_msg = HandleMsg::Mint {
-            recipient: allocation.config.receive_addr.to_string(),
-            amount: allocation_share,
-            padding: None,
-            memo: None,
-        };
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: erth_token.address.to_string(),
-            code_hash: erth_token.code_hash.clone(),
-            msg: to_binary(&mint_msg)?,
-            funds: vec![],
-        }));
-    };
+        let recipients = allocation_recipients(deps.storage, &allocation);
+        messages.extend(mint_to_recipients(&env, erth_token, allocation_id, &recipients, allocation_share)?);
+        messages.extend(transfer_to_recipients(erth_token, allocation_id, &recipients, bonus)?);
+    }
+    if !bonus.is_zero() {
+        ALLOCATION_BONUS_POOL.remove(deps.storage, &allocation_id)?;
+    }
 
     // Save the updated allocation and state
     ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
@@ -228,64 +402,88 @@ pub fn claim_allocation(
         .add_messages(messages)
         .add_attribute("action", "claim_allocation")
         .add_attribute("allocation_id", allocation_id.to_string())
-        .add_attribute("allocation_share", allocation_share.to_string()))
+        .add_attribute("allocation_share", allocation_share.to_string())
+        .add_attribute("bonus", bonus.to_string())
+        .add_attribute("vesting", vesting_duration.is_some().to_string()))
 }
 
+/// Update an allocation's description immediately. Manager and claimer changes go through
+/// the two-step handover, and payout destination changes wait out `PAYOUT_CHANGE_DELAY_SECONDS`.
 pub fn edit_allocation(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     allocation_id: u32,
     allocation_config: AllocationConfig,
-) -> StdResult<Response> {
-    let config = CONFIG.load(deps.storage)?;
-
+) -> Result<Response, ContractError> {
     let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
-        .ok_or_else(|| StdError::generic_err("Allocation not found"))?;
+        .ok_or(ContractError::AllocationNotFound)?;
 
     // Check if the sender is authorized to edit the allocation
-    if info.sender != config.contract_manager {
-        if let Some(manager_addr) = &allocation.config.manager_addr {
-            if &info.sender != manager_addr {
-                return Err(StdError::generic_err("Unauthorized: Only the allocation manager or contract manager can edit this allocation"));
-            }
-        } else {
-            return Err(StdError::generic_err("Unauthorized: Only the contract manager can edit this allocation"));
-        }
-    }
+    check_allocation_manager(deps.storage, &allocation, &info.sender)?;
 
-    if allocation_config.description.len() > MAX_DESCRIPTION_LENGTH {
-        return Err(StdError::generic_err(format!("Description exceeds max length of {}", MAX_DESCRIPTION_LENGTH)));
+    validate_allocation_config(deps.as_ref(), &allocation_config)?;
+
+    if allocation_config.manager_addr != allocation.config.manager_addr {
+        return Err(ContractError::ManagerChangeNeedsProposal);
+    }
+    if allocation_config.claimer_addr != allocation.config.claimer_addr {
+        return Err(ContractError::ClaimerChangeNeedsProposal);
     }
 
-    allocation.config = allocation_config;
+    // Editing back to the current destination cancels a pending change
+    let receive_changed = allocation_config.receive_addr != allocation.config.receive_addr
+        || allocation_config.receive_hash != allocation.config.receive_hash
+        || allocation_config.use_send != allocation.config.use_send;
+    let mut response = Response::new()
+        .add_attribute("action", "edit_allocation")
+        .add_attribute("allocation_id", allocation_id.to_string());
+    if receive_changed {
+        let effective_at = env.block.time.plus_seconds(PAYOUT_CHANGE_DELAY_SECONDS);
+        PENDING_RECEIVE_CHANGES.insert(deps.storage, &allocation_id, &PendingReceiveChange {
+            receive_addr: allocation_config.receive_addr,
+            receive_hash: allocation_config.receive_hash,
+            use_send: allocation_config.use_send,
+            effective_at,
+        })?;
+        response = response.add_attribute("receive_change_effective_at", effective_at.seconds().to_string());
+    } else if PENDING_RECEIVE_CHANGES.contains(deps.storage, &allocation_id) {
+        PENDING_RECEIVE_CHANGES.remove(deps.storage, &allocation_id)?;
+    }
 
+    allocation.config.description = allocation_config.description;
     ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
 
-    Ok(Response::new()
-        .add_attribute("action", "edit_allocation")
-        .add_attribute("allocation_id", allocation_id.to_string()))
+    Ok(response)
 }
 
 pub fn add_allocation(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    description: String,
-    receive_addr: Addr,
-    receive_hash: Option<String>,
-    manager_addr: Option<Addr>,
-    claimer_addr: Option<Addr>,
-    use_send: bool,
-) -> StdResult<Response> {
-    let config = CONFIG.load(deps.storage)?;
+    allocation_config: AllocationConfig,
+) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
 
-    if info.sender != config.contract_manager {
-        return Err(StdError::generic_err("Unauthorized: Only the contract manager can add an allocation"));
-    }
+    validate_allocation_config(deps.as_ref(), &allocation_config)?;
+
+    let allocation_id = create_allocation(deps.storage, &env, &mut state, allocation_config)?;
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_allocation")
+        .add_attribute("allocation_id", allocation_id.to_string()))
+}
 
-    if description.len() > MAX_DESCRIPTION_LENGTH {
-        return Err(StdError::generic_err(format!("Description exceeds max length of {}", MAX_DESCRIPTION_LENGTH)));
+/// Register a new allocation option, returning its id
+pub fn create_allocation(
+    storage: &mut dyn Storage,
+    env: &Env,
+    state: &mut State,
+    allocation_config: AllocationConfig,
+) -> Result<u32, ContractError> {
+    if allocation_config.description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(AllocationConfigError::DescriptionTooLong { max: MAX_DESCRIPTION_LENGTH }.into());
     }
 
     state.allocation_counter += 1;
@@ -298,55 +496,56 @@ pub fn add_allocation(
             accumulated_rewards: Uint128::zero(),
             last_reward_index: state.reward_index,
         },
-        config: AllocationConfig {
-            description,
-            receive_addr,
-            receive_hash,
-            manager_addr,
-            claimer_addr,
-            use_send,
-        },
+        config: allocation_config,
     };
 
-    ALLOCATION_OPTIONS.insert(deps.storage, &state.allocation_counter, &allocation)?;
+    ALLOCATION_OPTIONS.insert(storage, &state.allocation_counter, &allocation)?;
 
     // Add to ID list
-    let mut ids = ALLOCATION_IDS.load(deps.storage).unwrap_or_default();
+    let mut ids = ALLOCATION_IDS.load(storage).unwrap_or_default();
     ids.push(state.allocation_counter);
-    ALLOCATION_IDS.save(deps.storage, &ids)?;
-
-    STATE.save(deps.storage, &state)?;
+    ALLOCATION_IDS.save(storage, &ids)?;
 
-    Ok(Response::new()
-        .add_attribute("action", "add_allocation"))
+    Ok(state.allocation_counter)
 }
 
 pub fn reset_allocations(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-) -> StdResult<Response> {
-    let config = CONFIG.load(deps.storage)?;
-
-    if info.sender != config.contract_manager {
-        return Err(StdError::generic_err("Unauthorized: Only the contract manager can reset allocations"));
-    }
-
+) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
 
-    // Update global reward index one final time
-    update_reward_index(&mut state, env.block.time);
+    // Drop lapsed votes and update global reward index one final time
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
 
-    // Iterate all allocations: settle pending rewards, zero amount_allocated
+    // Iterate all allocations: settle pending rewards, snapshot closing tallies, zero amount_allocated
     let ids = ALLOCATION_IDS.load(deps.storage).unwrap_or_default();
+    let mut results = Vec::new();
     for id in &ids {
         if let Some(mut allocation) = ALLOCATION_OPTIONS.get(deps.storage, id) {
-            settle_allocation(&mut allocation.state, state.reward_index);
+            settle_and_track(deps.storage, &state, &mut allocation, 0, 0)?;
+            let stats = allocation_epoch_stats(deps.storage, *id, state.epoch);
+            results.push(EpochAllocationResult {
+                allocation_id: *id,
+                amount_allocated: allocation.state.amount_allocated,
+                voters: stats.voters,
+                rewards_earned: stats.rewards_earned,
+            });
             allocation.state.amount_allocated = Uint128::zero();
             ALLOCATION_OPTIONS.insert(deps.storage, id, &allocation)?;
         }
     }
 
+    let epoch_results = EpochResults {
+        epoch: state.epoch,
+        closed_at: env.block.time,
+        total_allocations: state.total_allocations,
+        rules: epoch_voting_rules(deps.storage, state.epoch),
+        allocations: results,
+    };
+    EPOCH_RESULTS.insert(deps.storage, &state.epoch, &epoch_results)?;
+    close_voter_epoch(deps.storage, state.epoch)?;
+
     // Reset total allocations and increment epoch
     state.total_allocations = Uint128::zero();
     state.epoch += 1;
@@ -357,3 +556,65 @@ pub fn reset_allocations(
         .add_attribute("action", "reset_allocations")
         .add_attribute("epoch", state.epoch.to_string()))
 }
+
+pub fn set_epoch_voting_rules(
+    deps: DepsMut,
+    epoch: u32,
+    rules: EpochVotingRules,
+) -> Result<Response, ContractError> {
+    // Rules can't change under votes that were already tallied with them
+    let state = STATE.load(deps.storage)?;
+    if epoch < state.epoch {
+        return Err(ContractError::PastEpochVotingRules);
+    }
+    if epoch == state.epoch && !state.total_allocations.is_zero() {
+        return Err(ContractError::EpochHasVotes);
+    }
+
+    EPOCH_VOTING_RULES.insert(deps.storage, &epoch, &rules)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_epoch_voting_rules")
+        .add_attribute("epoch", epoch.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Uint128;
+    use crate::error::ContractError;
+    use crate::msg::ExecuteMsg;
+    use crate::state::{STATE, ALLOCATION_OPTIONS, EpochVotingRules, TallyMode, PercentageScale};
+    use crate::testing::{setup, exec, register, add_allocation, vote, TestDeps, MANAGER};
+
+    fn tally(deps: &TestDeps, allocation_id: u32) -> Uint128 {
+        ALLOCATION_OPTIONS.get(deps.as_ref().storage, &allocation_id).unwrap().state.amount_allocated
+    }
+
+    #[test]
+    fn quadratic_tally_rewards_spreading_and_removes_exactly() {
+        let mut deps = setup();
+        let set_rules = ExecuteMsg::SetEpochVotingRules {
+            epoch: 0,
+            rules: EpochVotingRules { tally_mode: TallyMode::Quadratic, percentage_scale: PercentageScale::Percent },
+        };
+        exec(&mut deps, mock_env(), MANAGER, set_rules.clone()).unwrap();
+        let first = add_allocation(&mut deps, "first");
+        let second = add_allocation(&mut deps, "second");
+        register(&mut deps, mock_env(), "alice");
+        register(&mut deps, mock_env(), "bob");
+
+        // A full flat vote is 10,000 credits, sqrt(10,000 * 1e6) = 100,000; half is 70,710
+        vote(&mut deps, mock_env(), "alice", &[(first, 100)]).unwrap();
+        vote(&mut deps, mock_env(), "bob", &[(first, 50), (second, 50)]).unwrap();
+        assert_eq!(tally(&deps, first), Uint128::new(170_710));
+        assert_eq!(tally(&deps, second), Uint128::new(70_710));
+        assert_eq!(exec(&mut deps, mock_env(), MANAGER, set_rules), Err(ContractError::EpochHasVotes));
+
+        // Changing the vote takes off exactly what the split added
+        vote(&mut deps, mock_env(), "bob", &[(second, 100)]).unwrap();
+        assert_eq!(tally(&deps, first), Uint128::new(100_000));
+        assert_eq!(tally(&deps, second), Uint128::new(100_000));
+        assert_eq!(STATE.load(deps.as_ref().storage).unwrap().total_allocations, Uint128::new(200_000));
+    }
+}
@@ -0,0 +1,196 @@
+// src/execute/allocation_changes.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Addr, Storage, Timestamp};
+use crate::execute::roles::has_role;
+use crate::state::{Role, ALLOCATION_OPTIONS, Allocation, ALLOCATION_RECIPIENTS, PENDING_ALLOCATION_MANAGERS,
+    PENDING_ALLOCATION_CLAIMERS, PENDING_RECEIVE_CHANGES, PENDING_RECIPIENTS_CHANGES, PENDING_CLAIMER_REMOVALS,
+    PAYOUT_CHANGE_DELAY_SECONDS};
+use crate::error::ContractError;
+
+/// Only an allocation admin or the allocation's own manager may change an allocation
+pub fn check_allocation_manager(storage: &dyn Storage, allocation: &Allocation, sender: &Addr) -> Result<(), ContractError> {
+    if allocation.config.manager_addr.as_ref() != Some(sender) && !has_role(storage, sender, Role::AllocationAdmin)? {
+        return Err(ContractError::NotAllocationManager);
+    }
+    Ok(())
+}
+
+/// Apply payout changes whose delay has passed. The caller saves `allocation` if this returns true.
+pub fn apply_pending_payout(storage: &mut dyn Storage, allocation: &mut Allocation, now: Timestamp) -> StdResult<bool> {
+    let allocation_id = allocation.state.allocation_id;
+    let mut applied = false;
+
+    if let Some(change) = PENDING_RECEIVE_CHANGES.get(storage, &allocation_id) {
+        if change.effective_at <= now {
+            allocation.config.receive_addr = change.receive_addr;
+            allocation.config.receive_hash = change.receive_hash;
+            allocation.config.use_send = change.use_send;
+            PENDING_RECEIVE_CHANGES.remove(storage, &allocation_id)?;
+            applied = true;
+        }
+    }
+
+    if let Some(change) = PENDING_RECIPIENTS_CHANGES.get(storage, &allocation_id) {
+        if change.effective_at <= now {
+            if change.recipients.is_empty() {
+                if ALLOCATION_RECIPIENTS.contains(storage, &allocation_id) {
+                    ALLOCATION_RECIPIENTS.remove(storage, &allocation_id)?;
+                }
+            } else {
+                ALLOCATION_RECIPIENTS.insert(storage, &allocation_id, &change.recipients)?;
+            }
+            PENDING_RECIPIENTS_CHANGES.remove(storage, &allocation_id)?;
+        }
+    }
+
+    if let Some(effective_at) = PENDING_CLAIMER_REMOVALS.get(storage, &allocation_id) {
+        if effective_at <= now {
+            allocation.config.claimer_addr = None;
+            PENDING_CLAIMER_REMOVALS.remove(storage, &allocation_id)?;
+            applied = true;
+        }
+    }
+
+    Ok(applied)
+}
+
+/// First step of handing an allocation to a new manager; takes effect once they accept
+pub fn propose_allocation_manager(
+    deps: DepsMut,
+    info: MessageInfo,
+    allocation_id: u32,
+    manager: String,
+) -> Result<Response, ContractError> {
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+    check_allocation_manager(deps.storage, &allocation, &info.sender)?;
+
+    let manager = deps.api.addr_validate(&manager)?;
+    PENDING_ALLOCATION_MANAGERS.insert(deps.storage, &allocation_id, &manager)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_allocation_manager")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("pending_manager", manager.to_string()))
+}
+
+pub fn accept_allocation_manager(
+    deps: DepsMut,
+    info: MessageInfo,
+    allocation_id: u32,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_ALLOCATION_MANAGERS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::NoPendingAllocationManager)?;
+    if info.sender != pending {
+        return Err(ContractError::NotProposedAddress { role: "manager" });
+    }
+    let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+
+    allocation.config.manager_addr = Some(pending);
+    ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
+    PENDING_ALLOCATION_MANAGERS.remove(deps.storage, &allocation_id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_allocation_manager")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("manager", info.sender.to_string()))
+}
+
+/// Rotate the claimer. A new claimer has to accept; removing the claimer (`None`), which lets
+/// anyone trigger payouts to the recipients, waits out `PAYOUT_CHANGE_DELAY_SECONDS`. Either
+/// replaces whatever change was pending.
+pub fn propose_allocation_claimer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allocation_id: u32,
+    claimer: Option<String>,
+) -> Result<Response, ContractError> {
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+    check_allocation_manager(deps.storage, &allocation, &info.sender)?;
+
+    let response = Response::new()
+        .add_attribute("action", "propose_allocation_claimer")
+        .add_attribute("allocation_id", allocation_id.to_string());
+
+    match claimer {
+        Some(claimer) => {
+            let claimer = deps.api.addr_validate(&claimer)?;
+            PENDING_ALLOCATION_CLAIMERS.insert(deps.storage, &allocation_id, &claimer)?;
+            if PENDING_CLAIMER_REMOVALS.contains(deps.storage, &allocation_id) {
+                PENDING_CLAIMER_REMOVALS.remove(deps.storage, &allocation_id)?;
+            }
+            Ok(response.add_attribute("pending_claimer", claimer.to_string()))
+        }
+        None => {
+            if PENDING_ALLOCATION_CLAIMERS.contains(deps.storage, &allocation_id) {
+                PENDING_ALLOCATION_CLAIMERS.remove(deps.storage, &allocation_id)?;
+            }
+            if allocation.config.claimer_addr.is_none() {
+                if PENDING_CLAIMER_REMOVALS.contains(deps.storage, &allocation_id) {
+                    PENDING_CLAIMER_REMOVALS.remove(deps.storage, &allocation_id)?;
+                }
+                return Ok(response.add_attribute("claimer", "none"));
+            }
+            let effective_at = env.block.time.plus_seconds(PAYOUT_CHANGE_DELAY_SECONDS);
+            PENDING_CLAIMER_REMOVALS.insert(deps.storage, &allocation_id, &effective_at)?;
+            Ok(response
+                .add_attribute("claimer", "none")
+                .add_attribute("effective_at", effective_at.seconds().to_string()))
+        }
+    }
+}
+
+pub fn accept_allocation_claimer(
+    deps: DepsMut,
+    info: MessageInfo,
+    allocation_id: u32,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_ALLOCATION_CLAIMERS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::NoPendingAllocationClaimer)?;
+    if info.sender != pending {
+        return Err(ContractError::NotProposedAddress { role: "claimer" });
+    }
+    let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+
+    allocation.config.claimer_addr = Some(pending);
+    ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
+    PENDING_ALLOCATION_CLAIMERS.remove(deps.storage, &allocation_id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_allocation_claimer")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("claimer", info.sender.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use crate::error::ContractError;
+    use crate::msg::ExecuteMsg;
+    use crate::state::PAYOUT_CHANGE_DELAY_SECONDS;
+    use crate::testing::{setup, exec, add_allocation, MANAGER};
+
+    #[test]
+    fn removing_the_claimer_waits_out_the_payout_delay() {
+        let mut deps = setup();
+        let allocation_id = add_allocation(&mut deps, "project");
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::ProposeAllocationClaimer {
+            allocation_id,
+            claimer: Some("claimer".to_string()),
+        }).unwrap();
+        exec(&mut deps, mock_env(), "claimer", ExecuteMsg::AcceptAllocationClaimer { allocation_id }).unwrap();
+
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::ProposeAllocationClaimer { allocation_id, claimer: None }).unwrap();
+        let claim = ExecuteMsg::ClaimAllocation { allocation_id };
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(PAYOUT_CHANGE_DELAY_SECONDS - 1);
+        assert_eq!(exec(&mut deps, env.clone(), "anyone", claim.clone()), Err(ContractError::NotAllocationClaimer));
+        exec(&mut deps, env.clone(), "claimer", claim.clone()).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(1);
+        exec(&mut deps, env, "anyone", claim).unwrap();
+    }
+}
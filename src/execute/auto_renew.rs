@@ -0,0 +1,179 @@
+// src/execute/auto_renew.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128, Addr, Storage, Timestamp};
+use crate::state::{CONFIG, STATE, State, REGISTRATIONS, USER_ALLOCATIONS, USER_VOTES,
+    UserAllocations, UserVote, AllocationPercentage, AutoRenew, AutoRenewCursor, AUTO_RENEW, AUTO_RENEW_CURSOR,
+    AUTO_RENEW_PAGE_SIZE, DELEGATIONS};
+use crate::execute::allocation::{sync_allocations, apply_votes, epoch_voting_rules};
+use crate::execute::delegation::delegated_weight;
+use crate::execute::vote_expiry::enqueue_vote_expiry;
+use crate::execute::voting::voting_weight;
+use crate::execute::voter_reward::set_voter_weight;
+use crate::error::ContractError;
+
+/// Scale a user's previous allocations onto `total`. Proportions are kept whatever scale they
+/// were cast in; rounding leftovers go to the largest remainders so the result sums to exactly `total`.
+fn renormalize(allocations: Vec<AllocationPercentage>, total: Uint128) -> Vec<AllocationPercentage> {
+    let kept: Vec<AllocationPercentage> = allocations.into_iter()
+        .filter(|pct| !pct.percentage.is_zero())
+        .collect();
+    // Percentages are bounded by the scale total, so plain u128 math can't overflow
+    let sum: u128 = kept.iter().map(|pct| pct.percentage.u128()).sum();
+    if sum == 0 {
+        return vec![];
+    }
+
+    let mut renewed = Vec::with_capacity(kept.len());
+    let mut remainders = Vec::with_capacity(kept.len());
+    let mut assigned = 0u128;
+    for (i, pct) in kept.iter().enumerate() {
+        let exact = pct.percentage.u128() * total.u128();
+        assigned += exact / sum;
+        remainders.push((exact % sum, i));
+        renewed.push(AllocationPercentage { allocation_id: pct.allocation_id, percentage: Uint128::from(exact / sum) });
+    }
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let leftover = (total.u128() - assigned) as usize;
+    for (_, i) in remainders.into_iter().take(leftover) {
+        renewed[i].percentage += Uint128::one();
+    }
+    renewed.retain(|pct| !pct.percentage.is_zero());
+    renewed
+}
+
+/// Carry a user's last allocations into the current epoch if they enabled auto-renew,
+/// didn't opt out of this epoch and haven't voted or delegated. Returns whether a vote was applied.
+pub fn renew_votes(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    address: &Addr,
+    own_weight: Uint128,
+    expires: Timestamp,
+) -> Result<bool, ContractError> {
+    let auto_renew = match AUTO_RENEW.get(storage, address) {
+        Some(auto_renew) => auto_renew,
+        None => return Ok(false),
+    };
+    if auto_renew.skip_epoch == Some(state.epoch) || DELEGATIONS.contains(storage, address) || own_weight.is_zero() {
+        return Ok(false);
+    }
+    let user_data = USER_ALLOCATIONS.get(storage, address).unwrap_or_default();
+    if user_data.epoch >= state.epoch || user_data.allocations.is_empty() {
+        return Ok(false);
+    }
+
+    let total = epoch_voting_rules(storage, state.epoch).percentage_scale.total();
+    let allocations = renormalize(user_data.allocations, total);
+    if allocations.is_empty() {
+        return Ok(false);
+    }
+
+    let weight = own_weight + delegated_weight(storage, address);
+    apply_votes(storage, state, &allocations, weight)?;
+    set_voter_weight(storage, state.epoch, address, weight)?;
+    USER_ALLOCATIONS.insert(storage, address, &UserAllocations {
+        epoch: state.epoch,
+        allocations,
+    })?;
+    USER_VOTES.insert(storage, address, &UserVote {
+        epoch: state.epoch,
+        weight,
+        expires,
+    })?;
+    enqueue_vote_expiry(storage, address, expires)?;
+    Ok(true)
+}
+
+pub fn set_auto_renew(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    if enabled {
+        if !AUTO_RENEW.contains(deps.storage, &info.sender) {
+            AUTO_RENEW.insert(deps.storage, &info.sender, &AutoRenew { skip_epoch: None })?;
+        }
+    } else if AUTO_RENEW.contains(deps.storage, &info.sender) {
+        AUTO_RENEW.remove(deps.storage, &info.sender)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_auto_renew")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// Opt out of carrying allocations into a single epoch without disabling auto-renew.
+/// Allocations already carried into the current epoch stay until replaced by `SetAllocation`.
+pub fn skip_auto_renew(
+    deps: DepsMut,
+    info: MessageInfo,
+    epoch: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut auto_renew = AUTO_RENEW.get(deps.storage, &info.sender)
+        .ok_or(ContractError::AutoRenewNotEnabled)?;
+    let state = STATE.load(deps.storage)?;
+    if let Some(epoch) = epoch {
+        if epoch < state.epoch {
+            return Err(ContractError::SkipPastEpoch);
+        }
+    }
+
+    auto_renew.skip_epoch = epoch;
+    AUTO_RENEW.insert(deps.storage, &info.sender, &auto_renew)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "skip_auto_renew"))
+}
+
+/// Keeper entry point renewing one page of auto-renew users into the current epoch
+pub fn process_auto_renewals(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+    let page = AUTO_RENEW_CURSOR.may_load(deps.storage)?
+        .filter(|cursor| cursor.epoch == state.epoch)
+        .map(|cursor| cursor.page)
+        .unwrap_or(0);
+    let len = AUTO_RENEW.get_len(deps.storage)?;
+    let addresses = if page * AUTO_RENEW_PAGE_SIZE < len {
+        AUTO_RENEW.paging_keys(deps.storage, page, AUTO_RENEW_PAGE_SIZE)?
+    } else {
+        vec![]
+    };
+
+    let mut renewed = 0u32;
+    for address in &addresses {
+        let registration = match REGISTRATIONS.get_by_address(deps.storage, address)? {
+            Some(registration) => registration,
+            None => continue,
+        };
+        let expires = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
+        if env.block.time > expires {
+            continue;
+        }
+        // A failing balance snapshot skips the user rather than the whole page
+        let weight = match voting_weight(deps.as_ref(), &env, &config, &registration) {
+            Ok(weight) => weight,
+            Err(_) => continue,
+        };
+        if renew_votes(deps.storage, &mut state, address, weight, expires)? {
+            renewed += 1;
+        }
+    }
+
+    if !addresses.is_empty() {
+        AUTO_RENEW_CURSOR.save(deps.storage, &AutoRenewCursor {
+            epoch: state.epoch,
+            page: page + 1,
+        })?;
+    }
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "process_auto_renewals")
+        .add_attribute("renewed", renewed.to_string())
+        .add_attribute("done", addresses.is_empty().to_string()))
+}
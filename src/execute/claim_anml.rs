@@ -1,17 +1,22 @@
 use cosmwasm_std::{
-    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Timestamp, CosmosMsg, WasmMsg,
+    DepsMut, Env, MessageInfo, Response, Timestamp, CosmosMsg, WasmMsg,
     to_binary,
 };
-use crate::state::{REGISTRATIONS, CONFIG, STATE, query_registry};
+use crate::state::{REGISTRATIONS, CONFIG, STATE, PauseScope};
 use crate::msg::SendMsg;
 use secret_toolkit::snip20::HandleMsg;
-use crate::execute::allocation::update_reward_index;
+use crate::execute::allocation::{sync_allocations, reweight_votes};
+use crate::execute::voting::voting_weight;
+use crate::execute::auto_renew::renew_votes;
+use crate::execute::pause::is_paused;
+use crate::execute::registry::resolve_contracts;
+use crate::error::ContractError;
 
 pub fn claim_anml(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
 
     // Load config to get registration validity period
     let config = CONFIG.load(deps.storage)?;
@@ -22,21 +27,33 @@ pub fn claim_anml(
         // Check registration validity
         let registration_age = env.block.time.seconds() - registration.registration_timestamp.seconds();
         if registration_age > config.registration_validity_seconds {
-            return Err(StdError::generic_err("Registration has expired"));
+            return Err(ContractError::RegistrationExpired);
         }
 
         // Check last ANML claim time
         let elapsed_time = env.block.time.seconds() - registration.last_anml_claim.seconds();
         let seconds_in_a_day = 86400;
         if elapsed_time < seconds_in_a_day {
-            return Err(StdError::generic_err(
-                "One day hasn't passed since the last claim",
-            ));
+            return Err(ContractError::ClaimTooSoon);
         }
 
-        // Update global reward index (O(1) - no allocation iteration)
+        // Resolve the caller's current voting weight before touching storage; a failing
+        // balance snapshot shouldn't block the daily claim
+        let weight = voting_weight(deps.as_ref(), &env, &config, &registration).ok();
+
+        // Drop lapsed votes and update global reward index (bounded - no full allocation iteration)
         let mut state = STATE.load(deps.storage)?;
-        update_reward_index(&mut state, env.block.time);
+        sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+        // Carry the caller's allocations into a new epoch if they opted in, and keep
+        // their vote in line with their current weight. Tallies stay frozen while
+        // allocation voting is paused; the claim itself still goes through.
+        let voting_paused = is_paused(deps.storage, PauseScope::AllocationVoting)?;
+        if let Some(weight) = weight.filter(|_| !voting_paused) {
+            let expires = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
+            renew_votes(deps.storage, &mut state, &info.sender, weight, expires)?;
+            reweight_votes(deps.storage, &mut state, &info.sender, weight, expires)?;
+        }
 
         // Set last_anml_claim to midnight of the current day
         let midnight_timestamp = Timestamp::from_seconds(
@@ -53,52 +70,50 @@ pub fn claim_anml(
         )?;
 
         // Query registry for contract references
-        let deps_ref = deps.as_ref();
-        let contracts = query_registry(
-            &deps_ref,
-            &config.registry_contract,
-            &config.registry_hash,
-            vec!["erth_token", "anml_token", "exchange"],
-        )?;
+        let contracts = resolve_contracts(deps.branch(), env.block.time, &["erth_token", "anml_token", "exchange"])?;
         let erth_token = &contracts[0];
         let anml_token = &contracts[1];
         let exchange = &contracts[2];
 
-        let buyback_amount = (env.block.time.seconds() - state.last_anml_buyback.seconds()) * 1_000_000;
-
-        state.last_anml_buyback = env.block.time;
-
         let mut messages = vec![];
 
-        // Create messages for minting ERTH for the ANML buyback
-        let mint_erth = HandleMsg::Mint {
-            recipient: env.contract.address.to_string(),
-            amount: buyback_amount.into(),
-            padding: None,
-            memo: None,
-        };
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: erth_token.address.to_string(),
-            code_hash: erth_token.code_hash.clone(),
-            msg: to_binary(&mint_erth)?,
-            funds: vec![],
-        }));
-
-        // Swap Erth for ANML
-        let swap_msg = HandleMsg::Send {
-            recipient: exchange.address.to_string(),
-            recipient_code_hash: Some(exchange.code_hash.clone()),
-            amount: buyback_amount.into(),
-            msg: Some(to_binary(&SendMsg::AnmlBuybackSwap {})?),
-            memo: None,
-            padding: None,
-        };
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: erth_token.address.to_string(),
-            code_hash: erth_token.code_hash.clone(),
-            msg: to_binary(&swap_msg)?,
-            funds: vec![],
-        }));
+        // While buybacks are paused the amount keeps accruing and is swapped on the first claim after resuming
+        let mut buyback_amount = 0;
+        if !is_paused(deps.storage, PauseScope::Buybacks)? {
+            buyback_amount = (env.block.time.seconds() - state.last_anml_buyback.seconds()) * 1_000_000;
+
+            state.last_anml_buyback = env.block.time;
+
+            // Create messages for minting ERTH for the ANML buyback
+            let mint_erth = HandleMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: buyback_amount.into(),
+                padding: None,
+                memo: None,
+            };
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: erth_token.address.to_string(),
+                code_hash: erth_token.code_hash.clone(),
+                msg: to_binary(&mint_erth)?,
+                funds: vec![],
+            }));
+
+            // Swap Erth for ANML
+            let swap_msg = HandleMsg::Send {
+                recipient: exchange.address.to_string(),
+                recipient_code_hash: Some(exchange.code_hash.clone()),
+                amount: buyback_amount.into(),
+                msg: Some(to_binary(&SendMsg::AnmlBuybackSwap {})?),
+                memo: None,
+                padding: None,
+            };
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: erth_token.address.to_string(),
+                code_hash: erth_token.code_hash.clone(),
+                msg: to_binary(&swap_msg)?,
+                funds: vec![],
+            }));
+        }
 
         // Save state
         STATE.save(deps.storage, &state)?;
@@ -122,6 +137,6 @@ pub fn claim_anml(
             .add_attribute("action", "claim")
             .add_attribute("buyback_amount", buyback_amount.to_string()))
     } else {
-        Err(StdError::generic_err("User not registered"))
+        Err(ContractError::NotRegistered)
     }
 }
@@ -0,0 +1,316 @@
+// src/execute/delegation.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128, Addr, Storage};
+use crate::state::{CONFIG, STATE, State, REGISTRATIONS, USER_ALLOCATIONS, UserAllocations, Delegation,
+    DELEGATIONS, DELEGATED_WEIGHT, UPSTREAM_DELEGATORS, MAX_DELEGATION_DEPTH};
+use crate::execute::allocation::{sync_allocations, load_user_votes, remove_votes, set_vote_weight};
+use crate::execute::vote_expiry::enqueue_vote_expiry;
+use crate::execute::voting::voting_weight;
+use crate::execute::voter_reward::set_voter_weight;
+use crate::error::ContractError;
+
+/// Weight flowing into an address from the users delegating to it
+pub fn delegated_weight(storage: &dyn Storage, address: &Addr) -> Uint128 {
+    DELEGATED_WEIGHT.get(storage, address).unwrap_or_default()
+}
+
+/// Length of the longest delegation chain ending at `address`
+fn upstream_depth(storage: &dyn Storage, address: &Addr) -> usize {
+    UPSTREAM_DELEGATORS.get(storage, address).unwrap_or_default()
+        .iter()
+        .rposition(|count| *count > 0)
+        .map_or(0, |i| i + 1)
+}
+
+/// Add or remove `address` and everyone upstream of it in the upstream counts of each
+/// address along the chain starting at its `delegate`
+fn shift_upstream_delegators(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    delegate: &Addr,
+    add: bool,
+) -> Result<(), ContractError> {
+    // The address itself is one hop closer than its own delegators
+    let mut moving = vec![1u32];
+    moving.extend(UPSTREAM_DELEGATORS.get(storage, address).unwrap_or_default());
+
+    let mut node = delegate.clone();
+    let mut hops = 1;
+    loop {
+        let mut counts = UPSTREAM_DELEGATORS.get(storage, &node).unwrap_or_default();
+        if counts.len() < hops - 1 + moving.len() {
+            counts.resize(hops - 1 + moving.len(), 0);
+        }
+        for (i, count) in moving.iter().enumerate() {
+            let slot = &mut counts[hops - 1 + i];
+            *slot = if add {
+                slot.checked_add(*count).ok_or(ContractError::Overflow("upstream delegators"))?
+            } else {
+                slot.checked_sub(*count).ok_or(ContractError::Underflow("upstream delegators"))?
+            };
+        }
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+        if counts.is_empty() {
+            if UPSTREAM_DELEGATORS.contains(storage, &node) {
+                UPSTREAM_DELEGATORS.remove(storage, &node)?;
+            }
+        } else {
+            UPSTREAM_DELEGATORS.insert(storage, &node, &counts)?;
+        }
+
+        match DELEGATIONS.get(storage, &node) {
+            Some(delegation) => {
+                node = delegation.delegate;
+                hops += 1;
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Swap `remove` for `add` on every link of the delegation chain starting at `start`,
+/// re-tallying the vote of the address at the end of the chain
+pub fn shift_delegated_weight(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    start: &Addr,
+    remove: Uint128,
+    add: Uint128,
+) -> Result<(), ContractError> {
+    let mut node = start.clone();
+    loop {
+        let updated = delegated_weight(storage, &node).checked_sub(remove)
+            .map_err(|_| ContractError::Underflow("delegated weight"))? + add;
+        if updated.is_zero() {
+            if DELEGATED_WEIGHT.contains(storage, &node) {
+                DELEGATED_WEIGHT.remove(storage, &node)?;
+            }
+        } else {
+            DELEGATED_WEIGHT.insert(storage, &node, &updated)?;
+        }
+
+        match DELEGATIONS.get(storage, &node) {
+            Some(delegation) => node = delegation.delegate,
+            None => {
+                // Only the end of the chain has a vote carrying the delegated weight
+                let (allocations, weight) = load_user_votes(storage, &node, state.epoch);
+                if !allocations.is_empty() {
+                    let weight = weight.checked_sub(remove)
+                        .map_err(|_| ContractError::Underflow("vote weight"))? + add;
+                    set_vote_weight(storage, state, &node, weight)?;
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Stop following a delegate, taking the user's own weight and everything delegated
+/// through them out of the delegate's chain
+pub fn undelegate(storage: &mut dyn Storage, state: &mut State, address: &Addr) -> Result<(), ContractError> {
+    let delegation = DELEGATIONS.get(storage, address)
+        .ok_or(ContractError::NotDelegated)?;
+    let outflow = delegation.weight + delegated_weight(storage, address);
+    DELEGATIONS.remove(storage, address)?;
+    shift_upstream_delegators(storage, address, &delegation.delegate, false)?;
+    shift_delegated_weight(storage, state, &delegation.delegate, outflow, Uint128::zero())
+}
+
+pub fn delegate_allocation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegate: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let delegate_addr = deps.api.addr_validate(&delegate)?;
+
+    if delegate_addr == info.sender {
+        return Err(ContractError::SelfDelegation);
+    }
+
+    // Both sides must hold a valid registration
+    let registration = REGISTRATIONS.get_by_address(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotRegistered)?;
+    let expires = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
+    if env.block.time > expires {
+        return Err(ContractError::RegistrationExpired);
+    }
+    let delegate_registration = REGISTRATIONS.get_by_address(deps.storage, &delegate_addr)?
+        .ok_or(ContractError::DelegateNotRegistered)?;
+    if env.block.time > delegate_registration.registration_timestamp.plus_seconds(config.registration_validity_seconds) {
+        return Err(ContractError::DelegateRegistrationExpired);
+    }
+
+    // Walk the delegate's chain to reject cycles and overly long chains, counting the
+    // delegators already upstream of the user
+    let mut node = delegate_addr.clone();
+    let mut depth = upstream_depth(deps.storage, &info.sender) + 1;
+    if depth > MAX_DELEGATION_DEPTH {
+        return Err(ContractError::DelegationChainTooLong);
+    }
+    while let Some(next) = DELEGATIONS.get(deps.storage, &node) {
+        if next.delegate == info.sender {
+            return Err(ContractError::DelegationCycle);
+        }
+        depth += 1;
+        if depth > MAX_DELEGATION_DEPTH {
+            return Err(ContractError::DelegationChainTooLong);
+        }
+        node = next.delegate;
+    }
+
+    let weight = voting_weight(deps.as_ref(), &env, &config, &registration)?;
+    if weight.is_zero() {
+        return Err(ContractError::ZeroVotingWeight);
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+    // Leave the previous delegate, or withdraw the user's own vote for this epoch
+    if DELEGATIONS.contains(deps.storage, &info.sender) {
+        undelegate(deps.storage, &mut state, &info.sender)?;
+    } else {
+        let (allocations, old_weight) = load_user_votes(deps.storage, &info.sender, state.epoch);
+        if !allocations.is_empty() {
+            remove_votes(deps.storage, &mut state, &allocations, old_weight)?;
+            set_voter_weight(deps.storage, state.epoch, &info.sender, Uint128::zero())?;
+            USER_ALLOCATIONS.insert(deps.storage, &info.sender, &UserAllocations {
+                epoch: state.epoch,
+                allocations: vec![],
+            })?;
+        }
+    }
+
+    DELEGATIONS.insert(deps.storage, &info.sender, &Delegation {
+        delegate: delegate_addr.clone(),
+        weight,
+        expires,
+    })?;
+    enqueue_vote_expiry(deps.storage, &info.sender, expires)?;
+    shift_upstream_delegators(deps.storage, &info.sender, &delegate_addr, true)?;
+
+    // Everything delegated to the user now flows on to their delegate
+    let inflow = weight + delegated_weight(deps.storage, &info.sender);
+    shift_delegated_weight(deps.storage, &mut state, &delegate_addr, Uint128::zero(), inflow)?;
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "delegate_allocation")
+        .add_attribute("delegate", delegate_addr.to_string())
+        .add_attribute("weight", inflow.to_string()))
+}
+
+pub fn undelegate_allocation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+    undelegate(deps.storage, &mut state, &info.sender)?;
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "undelegate_allocation"))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{Addr, Uint128};
+    use crate::error::ContractError;
+    use crate::msg::ExecuteMsg;
+    use crate::state::{STATE, UPSTREAM_DELEGATORS, FLAT_VOTE_WEIGHT, MAX_DELEGATION_DEPTH};
+    use crate::execute::allocation::load_user_votes;
+    use crate::testing::{setup, exec, register, add_allocation, vote, TestDeps};
+    use super::delegated_weight;
+
+    fn delegate(deps: &mut TestDeps, from: &str, to: &str) -> Result<(), ContractError> {
+        exec(deps, mock_env(), from, ExecuteMsg::DelegateAllocation { delegate: to.to_string() }).map(|_| ())
+    }
+
+    fn delegated(deps: &TestDeps, address: &str) -> Uint128 {
+        delegated_weight(deps.as_ref().storage, &Addr::unchecked(address))
+    }
+
+    fn vote_weight(deps: &TestDeps, address: &str) -> Uint128 {
+        let epoch = STATE.load(deps.as_ref().storage).unwrap().epoch;
+        load_user_votes(deps.as_ref().storage, &Addr::unchecked(address), epoch).1
+    }
+
+    fn upstream(deps: &TestDeps, address: &str) -> Vec<u32> {
+        UPSTREAM_DELEGATORS.get(deps.as_ref().storage, &Addr::unchecked(address)).unwrap_or_default()
+    }
+
+    #[test]
+    fn redelegating_and_undelegating_mid_chain() {
+        let mut deps = setup();
+        let w = Uint128::from(FLAT_VOTE_WEIGHT);
+        let allocation_id = add_allocation(&mut deps, "project");
+        for address in ["alice", "bob", "carol", "dave", "erin"] {
+            register(&mut deps, mock_env(), address);
+        }
+        vote(&mut deps, mock_env(), "dave", &[(allocation_id, 100)]).unwrap();
+        vote(&mut deps, mock_env(), "erin", &[(allocation_id, 100)]).unwrap();
+        delegate(&mut deps, "carol", "dave").unwrap();
+        delegate(&mut deps, "bob", "carol").unwrap();
+        delegate(&mut deps, "alice", "bob").unwrap();
+        assert_eq!(vote_weight(&deps, "dave"), w * Uint128::new(4));
+        assert_eq!(upstream(&deps, "dave"), vec![1, 1, 1]);
+
+        // bob takes alice along to erin; carol keeps only its own weight flowing to dave
+        delegate(&mut deps, "bob", "erin").unwrap();
+        assert_eq!(delegated(&deps, "carol"), Uint128::zero());
+        assert_eq!(delegated(&deps, "dave"), w);
+        assert_eq!(delegated(&deps, "erin"), w * Uint128::new(2));
+        assert_eq!(vote_weight(&deps, "dave"), w * Uint128::new(2));
+        assert_eq!(vote_weight(&deps, "erin"), w * Uint128::new(3));
+        assert_eq!(upstream(&deps, "carol"), Vec::<u32>::new());
+        assert_eq!(upstream(&deps, "dave"), vec![1]);
+        assert_eq!(upstream(&deps, "erin"), vec![1, 1]);
+
+        // Leaving erin keeps alice delegating to bob, which now votes for nobody
+        exec(&mut deps, mock_env(), "bob", ExecuteMsg::UndelegateAllocation {}).unwrap();
+        assert_eq!(delegated(&deps, "bob"), w);
+        assert_eq!(delegated(&deps, "erin"), Uint128::zero());
+        assert_eq!(vote_weight(&deps, "erin"), w);
+        assert_eq!(vote_weight(&deps, "dave"), w * Uint128::new(2));
+        assert_eq!(upstream(&deps, "bob"), vec![1]);
+        assert_eq!(upstream(&deps, "erin"), Vec::<u32>::new());
+
+        // Both put 100% (10,000 basis points) on the allocation, credited as weight * 100
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        let voted = vote_weight(&deps, "dave") + vote_weight(&deps, "erin");
+        assert_eq!(state.total_allocations, voted * Uint128::new(100));
+    }
+
+    #[test]
+    fn chain_depth_counts_upstream_delegators() {
+        let mut deps = setup();
+        let chain: Vec<String> = (0..MAX_DELEGATION_DEPTH).map(|i| format!("link{}", i)).collect();
+        for address in chain.iter().map(String::as_str).chain(["tail", "target", "fresh"]) {
+            register(&mut deps, mock_env(), address);
+        }
+        for pair in chain.windows(2) {
+            delegate(&mut deps, &pair[0], &pair[1]).unwrap();
+        }
+        // The chain ending at tail is now as long as allowed
+        delegate(&mut deps, &chain[MAX_DELEGATION_DEPTH - 1], "tail").unwrap();
+        assert_eq!(upstream(&deps, "tail").len(), MAX_DELEGATION_DEPTH);
+
+        // tail's own step is a single hop, but everyone behind it would move one further
+        assert_eq!(delegate(&mut deps, "tail", "target"), Err(ContractError::DelegationChainTooLong));
+        delegate(&mut deps, "fresh", "target").unwrap();
+
+        // Once the far end leaves, there is room again
+        exec(&mut deps, mock_env(), &chain[0], ExecuteMsg::UndelegateAllocation {}).unwrap();
+        delegate(&mut deps, "tail", "target").unwrap();
+        assert_eq!(upstream(&deps, "target").len(), MAX_DELEGATION_DEPTH);
+    }
+}
@@ -0,0 +1,162 @@
+// src/execute/invariants.rs
+use std::collections::BTreeSet;
+use cosmwasm_std::{Deps, DepsMut, Env, Response, StdResult, Storage, Uint128};
+use crate::msg::{InvariantCheck, InvariantScope, InvariantReport, InvariantViolation};
+use crate::state::{Allocation, State, STATE, ALLOCATION_OPTIONS, ALLOCATION_IDS, USER_ALLOCATIONS, REGISTRATIONS};
+use crate::execute::allocation::{sync_allocations, epoch_voting_rules};
+use crate::error::ContractError;
+
+/// Consistency of the allocation list with the totals recorded in `State`
+pub fn allocation_invariants(allocations: &[Allocation], ids: &[u32], state: &State) -> Vec<InvariantCheck> {
+    let allocated = allocations.iter()
+        .fold(Uint128::zero(), |acc, a| acc + a.state.amount_allocated);
+    let mut unique_ids = ids.to_vec();
+    unique_ids.sort_unstable();
+    unique_ids.dedup();
+
+    vec![
+        InvariantCheck {
+            name: "sum of amount_allocated == total_allocations".to_string(),
+            holds: allocated == state.total_allocations,
+        },
+        InvariantCheck {
+            name: "allocation ids are unique".to_string(),
+            holds: unique_ids.len() == ids.len(),
+        },
+        InvariantCheck {
+            name: "every allocation id is listed in allocation_ids".to_string(),
+            holds: allocations.len() == ids.len()
+                && allocations.iter().all(|a| unique_ids.binary_search(&a.state.allocation_id).is_ok()),
+        },
+        InvariantCheck {
+            name: "allocation_counter >= highest allocation id".to_string(),
+            holds: ids.iter().all(|id| *id <= state.allocation_counter),
+        },
+    ]
+}
+
+fn violation(invariant: &str, key: impl ToString) -> InvariantViolation {
+    InvariantViolation {
+        invariant: invariant.to_string(),
+        key: Some(key.to_string()),
+    }
+}
+
+fn load_allocations(storage: &dyn Storage) -> StdResult<Vec<Allocation>> {
+    ALLOCATION_OPTIONS.iter(storage)?
+        .map(|entry| entry.map(|(_, allocation)| allocation))
+        .collect()
+}
+
+/// Check one page of a scope; `Totals` covers every allocation at once and ignores paging
+pub fn check_invariants(deps: Deps, scope: InvariantScope, page: u32, limit: u32) -> StdResult<InvariantReport> {
+    let state = STATE.load(deps.storage)?;
+    let mut violations = Vec::new();
+
+    let (checked, total) = match scope {
+        InvariantScope::Totals => {
+            let allocations = load_allocations(deps.storage)?;
+            let ids = ALLOCATION_IDS.may_load(deps.storage)?.unwrap_or_default();
+            violations.extend(allocation_invariants(&allocations, &ids, &state).into_iter()
+                .filter(|check| !check.holds)
+                .map(|check| InvariantViolation { invariant: check.name, key: None }));
+            (allocations.len() as u32, 0)
+        }
+        InvariantScope::UserAllocations => {
+            let scale_total = epoch_voting_rules(deps.storage, state.epoch).percentage_scale.total();
+            let sum_invariant = format!("current-epoch allocations sum to {}", scale_total);
+            let entries = USER_ALLOCATIONS.paging(deps.storage, page, limit)?;
+            for (address, user_allocations) in &entries {
+                // Earlier epochs' votes no longer count and are replaced on the next vote
+                if user_allocations.epoch != state.epoch || user_allocations.allocations.is_empty() {
+                    continue;
+                }
+                let sum = user_allocations.allocations.iter()
+                    .fold(Uint128::zero(), |acc, pct| acc + pct.percentage);
+                if sum != scale_total {
+                    violations.push(violation(&sum_invariant, address));
+                }
+                let ids: BTreeSet<u32> = user_allocations.allocations.iter().map(|pct| pct.allocation_id).collect();
+                if ids.len() != user_allocations.allocations.len() {
+                    violations.push(violation("allocation ids are unique per user", address));
+                }
+                if !ids.iter().all(|id| ALLOCATION_OPTIONS.contains(deps.storage, id)) {
+                    violations.push(violation("user allocations reference existing allocations", address));
+                }
+            }
+            (entries.len() as u32, USER_ALLOCATIONS.get_len(deps.storage)?)
+        }
+        InvariantScope::Registrations => {
+            let entries = REGISTRATIONS.by_address.paging(deps.storage, page, limit)?;
+            for (address, registration) in &entries {
+                if registration.address != *address {
+                    violations.push(violation("registration address matches its key", address));
+                }
+                let matches = REGISTRATIONS.by_hash.get(deps.storage, &registration.id_hash)
+                    .is_some_and(|by_hash| by_hash == *registration);
+                if !matches {
+                    violations.push(violation("by_hash holds the same registration as by_address", address));
+                }
+            }
+            (entries.len() as u32, REGISTRATIONS.by_address.get_len(deps.storage)?)
+        }
+        InvariantScope::RegistrationHashes => {
+            let entries = REGISTRATIONS.by_hash.paging(deps.storage, page, limit)?;
+            for (id_hash, registration) in &entries {
+                if registration.id_hash != *id_hash {
+                    violations.push(violation("registration id_hash matches its key", id_hash));
+                }
+                let matches = REGISTRATIONS.by_address.get(deps.storage, &registration.address)
+                    .is_some_and(|by_address| by_address == *registration);
+                if !matches {
+                    violations.push(violation("by_address holds the same registration as by_hash", id_hash));
+                }
+            }
+            (entries.len() as u32, REGISTRATIONS.by_hash.get_len(deps.storage)?)
+        }
+    };
+
+    let more = scope != InvariantScope::Totals
+        && (page as u64 + 1) * (limit as u64) < total as u64;
+
+    Ok(InvariantReport { scope, page, checked, more, violations })
+}
+
+/// Recompute `total_allocations` from the allocations and rebuild the allocation id list.
+/// Rewards up to now are accrued at the recorded total before it is replaced.
+pub fn repair_totals(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
+    let allocations = load_allocations(deps.storage)?;
+    let mut total_allocations = Uint128::zero();
+    for allocation in &allocations {
+        total_allocations = total_allocations.checked_add(allocation.state.amount_allocated)
+            .map_err(|_| ContractError::Overflow("total allocations"))?;
+    }
+    let mut ids: Vec<u32> = allocations.iter().map(|a| a.state.allocation_id).collect();
+    ids.sort_unstable();
+
+    let old_ids = ALLOCATION_IDS.may_load(deps.storage)?.unwrap_or_default();
+    let ids_repaired = old_ids != ids;
+    if ids_repaired {
+        ALLOCATION_IDS.save(deps.storage, &ids)?;
+    }
+
+    let old_total = state.total_allocations;
+    let old_counter = state.allocation_counter;
+    state.total_allocations = total_allocations;
+    state.allocation_counter = ids.last().copied().unwrap_or(0).max(state.allocation_counter);
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "repair_totals")
+        .add_attribute("old_total_allocations", old_total.to_string())
+        .add_attribute("new_total_allocations", total_allocations.to_string())
+        .add_attribute("old_allocation_counter", old_counter.to_string())
+        .add_attribute("new_allocation_counter", state.allocation_counter.to_string())
+        .add_attribute("allocation_ids_repaired", ids_repaired.to_string()))
+}
@@ -4,26 +4,149 @@ pub mod registration;
 pub mod allocation;
 pub mod claim_anml;
 pub mod receive;
+pub mod voting;
+pub mod vote_expiry;
+pub mod delegation;
+pub mod auto_renew;
+pub mod proposal;
+pub mod recipients;
+pub mod vesting;
+pub mod reward_stream;
+pub mod voter_reward;
+pub mod allocation_changes;
+pub mod validation;
+pub mod timelock;
+pub mod roles;
+pub mod pause;
+pub mod registry;
+pub mod invariants;
 
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use crate::msg::ExecuteMsg;
+use crate::state::Role;
+use crate::error::ContractError;
 
 pub fn execute_dispatch(
     deps: DepsMut, 
     env: Env, 
     info: MessageInfo, 
     msg: ExecuteMsg
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    if let Some(role) = roles::required_role(&msg) {
+        roles::check_role(deps.storage, &info.sender, role)?;
+    }
+    if let Some(scope) = pause::pause_scope(&msg) {
+        pause::ensure_not_paused(deps.storage, scope)?;
+    }
+
     match msg {
-        ExecuteMsg::UpdateConfig { config } => update_config::update_config(deps, env, info, config),
-        ExecuteMsg::Register { address, id_hash, affiliate } => registration::register(deps, env, info, address, id_hash, affiliate),
+        ExecuteMsg::UpdateConfig(update) => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            update_config::update_config(deps, env, update)
+        }
+        ExecuteMsg::ProposeManager { address } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            update_config::propose_manager(deps, env, info, address)
+        }
+        ExecuteMsg::AcceptManager {} => update_config::accept_manager(deps, env, info),
+        ExecuteMsg::CancelManagerProposal {} => update_config::cancel_manager_proposal(deps, env, info),
+        ExecuteMsg::Register { address, id_hash, affiliate } => registration::register(deps, env, address, id_hash, affiliate),
         ExecuteMsg::ClaimAnml {} => claim_anml::claim_anml(deps, env, info),
         ExecuteMsg::SetAllocation { percentages } => allocation::set_allocation(deps, env, info, percentages),
         ExecuteMsg::ClaimAllocation { allocation_id } => allocation::claim_allocation(deps, env, info, allocation_id),
-        ExecuteMsg::EditAllocation { allocation_id, config } => allocation::edit_allocation(deps, info, allocation_id, config),
-        ExecuteMsg::ResetAllocations {} => allocation::reset_allocations(deps, env, info),
-        ExecuteMsg::AddAllocation { description, receive_addr, receive_hash, manager_addr, claimer_addr, use_send } =>
-            allocation::add_allocation(deps, env, info, description, receive_addr, receive_hash, manager_addr, claimer_addr, use_send),
+        ExecuteMsg::EditAllocation { allocation_id, config } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            allocation::edit_allocation(deps, env, info, allocation_id, config)
+        }
+        ExecuteMsg::ResetAllocations {} => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            allocation::reset_allocations(deps, env)
+        }
+        ExecuteMsg::UpdateVotingConfig { config } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            update_config::update_voting_config(deps, env, config)
+        }
+        ExecuteMsg::RefreshAllocationWeight { address } => voting::refresh_allocation_weight(deps, env, address),
+        ExecuteMsg::SetEpochVotingRules { epoch, rules } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            allocation::set_epoch_voting_rules(deps, epoch, rules)
+        }
+        ExecuteMsg::ProcessVoteExpiries { limit } => vote_expiry::process_vote_expiries_msg(deps, env, limit),
+        ExecuteMsg::DelegateAllocation { delegate } => delegation::delegate_allocation(deps, env, info, delegate),
+        ExecuteMsg::UndelegateAllocation {} => delegation::undelegate_allocation(deps, env, info),
+        ExecuteMsg::SetAutoRenew { enabled } => auto_renew::set_auto_renew(deps, info, enabled),
+        ExecuteMsg::SkipAutoRenew { epoch } => auto_renew::skip_auto_renew(deps, info, epoch),
+        ExecuteMsg::ProcessAutoRenewals {} => auto_renew::process_auto_renewals(deps, env),
+        ExecuteMsg::EndorseProposal { proposal_id } => proposal::endorse_proposal(deps, env, info, proposal_id),
+        ExecuteMsg::ApproveProposal { proposal_id } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            proposal::approve_proposal(deps, env, proposal_id)
+        }
+        ExecuteMsg::RejectProposal { proposal_id, slash } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            proposal::reject_proposal(deps, env, proposal_id, slash)
+        }
+        ExecuteMsg::SetProposalConfig { config } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            proposal::set_proposal_config(deps, config)
+        }
+        ExecuteMsg::SetAllocationRecipients { allocation_id, recipients } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            recipients::set_allocation_recipients(deps, env, info, allocation_id, recipients)
+        }
+        ExecuteMsg::SetAllocationVesting { allocation_id, duration_seconds } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            vesting::set_allocation_vesting(deps, allocation_id, duration_seconds)
+        }
+        ExecuteMsg::WithdrawVested { allocation_id } => vesting::withdraw_vested(deps, env, info, allocation_id),
+        ExecuteMsg::RegisterRewardToken { token, code_hash } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            reward_stream::register_reward_token(deps, env, token, code_hash)
+        }
+        ExecuteMsg::ClaimAllocationToken { allocation_id, token } =>
+            reward_stream::claim_allocation_token(deps, env, info, allocation_id, token),
+        ExecuteMsg::ProposeAllocationManager { allocation_id, manager } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            allocation_changes::propose_allocation_manager(deps, info, allocation_id, manager)
+        }
+        ExecuteMsg::AcceptAllocationManager { allocation_id } =>
+            allocation_changes::accept_allocation_manager(deps, info, allocation_id),
+        ExecuteMsg::ProposeAllocationClaimer { allocation_id, claimer } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            allocation_changes::propose_allocation_claimer(deps, env, info, allocation_id, claimer)
+        }
+        ExecuteMsg::AcceptAllocationClaimer { allocation_id } =>
+            allocation_changes::accept_allocation_claimer(deps, info, allocation_id),
+        ExecuteMsg::ClaimVoterRewards {} => voter_reward::claim_voter_rewards(deps, env, info),
+        ExecuteMsg::ClaimVoterTokenRewards { token } =>
+            reward_stream::claim_voter_token_rewards(deps, env, info, token),
+        ExecuteMsg::SetVoterRewardConfig { config } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            voter_reward::set_voter_reward_config(deps, env, config)
+        }
+        ExecuteMsg::AddAllocation { config } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::AllocationAdmin)?;
+            allocation::add_allocation(deps, env, config)
+        }
+        ExecuteMsg::SetAdminTimelock { delay_seconds } => timelock::set_admin_timelock(deps, info, delay_seconds),
+        ExecuteMsg::QueueAdminAction { action } => timelock::queue_admin_action(deps, env, action),
+        ExecuteMsg::ExecuteAdminAction { id } => timelock::execute_admin_action(deps, env, info, id),
+        ExecuteMsg::CancelAdminAction { id } => timelock::cancel_admin_action(deps, id),
+        ExecuteMsg::GrantRole { address, role } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            roles::grant_role(deps, info, address, role)
+        }
+        ExecuteMsg::RevokeRole { address, role } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            roles::revoke_role(deps, info, address, role)
+        }
+        ExecuteMsg::SetPause { scope, paused } => pause::set_pause(deps, scope, paused),
+        ExecuteMsg::RefreshRegistry {} => registry::refresh_registry(deps, env),
+        ExecuteMsg::RepairTotals {} => invariants::repair_totals(deps, env),
+        ExecuteMsg::SetRegistryPin { name, contract } => {
+            timelock::ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+            registry::set_registry_pin(deps, name, contract)
+        }
         ExecuteMsg::Receive { sender, from, amount, msg, memo: _ } => 
             receive::receive(deps, env, info, sender, from, amount, msg),
     }
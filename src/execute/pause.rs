@@ -0,0 +1,140 @@
+// src/execute/pause.rs
+use cosmwasm_std::{DepsMut, Response, StdResult, Storage};
+use crate::state::{PauseScope, PauseStatus, PAUSE_STATUS};
+use crate::msg::ExecuteMsg;
+use crate::error::ContractError;
+
+pub fn pause_status(storage: &dyn Storage) -> StdResult<PauseStatus> {
+    Ok(PAUSE_STATUS.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn is_paused(storage: &dyn Storage, scope: PauseScope) -> StdResult<bool> {
+    Ok(pause_status(storage)?.is_paused(scope))
+}
+
+pub fn ensure_not_paused(storage: &dyn Storage, scope: PauseScope) -> Result<(), ContractError> {
+    if is_paused(storage, scope)? {
+        return Err(ContractError::Paused { scope });
+    }
+    Ok(())
+}
+
+/// Scope that stops an entry point. Buybacks and vote renewals are skipped inside `claim_anml`
+/// rather than rejecting the claim, `receive` checks the scope of each hook, and admin entry
+/// points stay available to respond to the incident.
+pub fn pause_scope(msg: &ExecuteMsg) -> Option<PauseScope> {
+    match msg {
+        ExecuteMsg::Register { .. } => Some(PauseScope::Registration),
+
+        ExecuteMsg::ClaimAnml {} => Some(PauseScope::AnmlClaims),
+
+        ExecuteMsg::SetAllocation { .. }
+        | ExecuteMsg::DelegateAllocation { .. }
+        | ExecuteMsg::UndelegateAllocation {}
+        | ExecuteMsg::RefreshAllocationWeight { .. }
+        | ExecuteMsg::SetAutoRenew { .. }
+        | ExecuteMsg::SkipAutoRenew { .. }
+        | ExecuteMsg::ProcessAutoRenewals {}
+        | ExecuteMsg::EndorseProposal { .. } => Some(PauseScope::AllocationVoting),
+
+        ExecuteMsg::ClaimAllocation { .. }
+        | ExecuteMsg::WithdrawVested { .. }
+        | ExecuteMsg::ClaimAllocationToken { .. }
+        | ExecuteMsg::ClaimVoterRewards {}
+        | ExecuteMsg::ClaimVoterTokenRewards { .. } => Some(PauseScope::AllocationClaims),
+
+        _ => None,
+    }
+}
+
+pub fn set_pause(
+    deps: DepsMut,
+    scope: PauseScope,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut status = pause_status(deps.storage)?;
+    if status.is_paused(scope) == paused {
+        return Err(ContractError::PauseUnchanged { scope, paused });
+    }
+    status.set(scope, paused);
+    PAUSE_STATUS.save(deps.storage, &status)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_pause")
+        .add_attribute("scope", format!("{:?}", scope))
+        .add_attribute("paused", paused.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use crate::msg::{ExecuteMsg, ProposeAllocationMsg, ReceiveMsg};
+    use crate::state::{PauseScope, VotingConfig, VotingWeightMode, ALLOCATION_OPTIONS};
+    use crate::error::ContractError;
+    use crate::testing::{setup, exec, register, receive_erth, add_allocation, vote, TestDeps, MANAGER};
+
+    fn pause_voting(deps: &mut TestDeps) {
+        exec(deps, mock_env(), MANAGER, ExecuteMsg::SetPause {
+            scope: PauseScope::AllocationVoting,
+            paused: true,
+        }).unwrap();
+    }
+
+    #[test]
+    fn voting_pause_covers_every_vote_change() {
+        let mut deps = setup();
+        register(&mut deps, mock_env(), "voter");
+        pause_voting(&mut deps);
+
+        let paused = ContractError::Paused { scope: PauseScope::AllocationVoting };
+        for msg in [
+            ExecuteMsg::RefreshAllocationWeight { address: "voter".to_string() },
+            ExecuteMsg::SetAutoRenew { enabled: true },
+            ExecuteMsg::SkipAutoRenew { epoch: Some(1) },
+            ExecuteMsg::ProcessAutoRenewals {},
+        ] {
+            let sender = if msg == (ExecuteMsg::ProcessAutoRenewals {}) { MANAGER } else { "voter" };
+            assert_eq!(exec(&mut deps, mock_env(), sender, msg).unwrap_err(), paused);
+        }
+
+        let proposal = ReceiveMsg::ProposeAllocation(ProposeAllocationMsg {
+            description: String::new(),
+            receive_addr: "project".to_string(),
+            receive_hash: None,
+            manager_addr: None,
+            claimer_addr: None,
+            use_send: false,
+        });
+        assert_eq!(receive_erth(&mut deps, mock_env(), "voter", 1_000, &proposal).unwrap_err(), paused);
+    }
+
+    #[test]
+    fn claim_anml_leaves_votes_alone_while_voting_is_paused() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::UpdateVotingConfig {
+            config: VotingConfig { weight_mode: VotingWeightMode::Seniority },
+        }).unwrap();
+        let allocation_id = add_allocation(&mut deps, "project");
+        register(&mut deps, mock_env(), "voter");
+        vote(&mut deps, mock_env(), "voter", &[(allocation_id, 100)]).unwrap();
+        let tally = |deps: &TestDeps| {
+            ALLOCATION_OPTIONS.get(deps.as_ref().storage, &allocation_id).unwrap().state.amount_allocated
+        };
+        let before = tally(&deps);
+
+        // A day of seniority would raise the vote's weight on claim
+        pause_voting(&mut deps);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2 * 86_400);
+        exec(&mut deps, env.clone(), "voter", ExecuteMsg::ClaimAnml {}).unwrap();
+        assert_eq!(tally(&deps), before);
+
+        exec(&mut deps, env.clone(), MANAGER, ExecuteMsg::SetPause {
+            scope: PauseScope::AllocationVoting,
+            paused: false,
+        }).unwrap();
+        env.block.time = env.block.time.plus_seconds(86_400);
+        exec(&mut deps, env, "voter", ExecuteMsg::ClaimAnml {}).unwrap();
+        assert!(tally(&deps) > before);
+    }
+}
@@ -0,0 +1,229 @@
+// src/execute/proposal.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Addr, Storage,
+    to_binary, CosmosMsg, WasmMsg};
+use secret_toolkit::snip20::HandleMsg;
+use crate::state::{CONFIG, STATE, State, REGISTRATIONS, AllocationConfig, AllocationProposal, ProposalStatus,
+    ProposalConfig, PROPOSAL_CONFIG, PROPOSALS, PROPOSAL_COUNTER, PROPOSAL_ENDORSEMENTS, MIN_ENDORSEMENT_THRESHOLD};
+use crate::msg::ProposeAllocationMsg;
+use crate::execute::allocation::create_allocation;
+use crate::execute::receive::verify_erth_sender;
+use crate::execute::registry::resolve_contracts;
+use crate::execute::validation::validate_allocation_config;
+use crate::error::ContractError;
+
+/// Submit an allocation option through the ERTH Receive hook; the sent amount is held as a deposit
+pub fn propose_allocation(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from: Addr,
+    amount: Uint128,
+    proposal: ProposeAllocationMsg,
+) -> Result<Response, ContractError> {
+    verify_erth_sender(deps.branch(), &env, &info)?;
+
+    let proposal_config = PROPOSAL_CONFIG.may_load(deps.storage)?
+        .ok_or(ContractError::ProposalsDisabled)?;
+    if amount < proposal_config.min_deposit {
+        return Err(ContractError::DepositTooLow { min: proposal_config.min_deposit.to_string() });
+    }
+    let config = AllocationConfig {
+        description: proposal.description,
+        receive_addr: deps.api.addr_validate(&proposal.receive_addr)?,
+        receive_hash: proposal.receive_hash,
+        manager_addr: proposal.manager_addr.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        claimer_addr: proposal.claimer_addr.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        use_send: proposal.use_send,
+    };
+    validate_allocation_config(deps.as_ref(), &config)?;
+
+    let proposal_id = PROPOSAL_COUNTER.may_load(deps.storage)?.unwrap_or(0) + 1;
+    PROPOSAL_COUNTER.save(deps.storage, &proposal_id)?;
+
+    PROPOSALS.insert(deps.storage, &proposal_id, &AllocationProposal {
+        proposal_id,
+        proposer: from,
+        deposit: amount,
+        config,
+        endorsements: 0,
+        status: ProposalStatus::Pending,
+        submitted: env.block.time,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_allocation")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("deposit", amount.to_string()))
+}
+
+fn load_pending(storage: &dyn Storage, proposal_id: u32) -> Result<AllocationProposal, ContractError> {
+    let proposal = PROPOSALS.get(storage, &proposal_id)
+        .ok_or(ContractError::ProposalNotFound)?;
+    if proposal.status != ProposalStatus::Pending {
+        return Err(ContractError::ProposalDecided);
+    }
+    Ok(proposal)
+}
+
+/// Transfer a held deposit back to its proposer
+fn refund_deposit(deps: DepsMut, env: &Env, proposal: &AllocationProposal) -> StdResult<Option<CosmosMsg>> {
+    if proposal.deposit.is_zero() {
+        return Ok(None);
+    }
+    let contracts = resolve_contracts(deps, env.block.time, &["erth_token"])?;
+    let erth_token = &contracts[0];
+
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: erth_token.address.to_string(),
+        code_hash: erth_token.code_hash.clone(),
+        msg: to_binary(&HandleMsg::Transfer {
+            recipient: proposal.proposer.to_string(),
+            amount: proposal.deposit,
+            memo: None,
+            padding: None,
+        })?,
+        funds: vec![],
+    })))
+}
+
+/// Turn a pending proposal into an allocation option and refund its deposit
+fn approve(deps: &mut DepsMut, env: &Env, state: &mut State, mut proposal: AllocationProposal) -> Result<Response, ContractError> {
+    let allocation_id = create_allocation(deps.storage, env, state, proposal.config.clone())?;
+    proposal.status = ProposalStatus::Approved { allocation_id };
+    PROPOSALS.insert(deps.storage, &proposal.proposal_id, &proposal)?;
+
+    let refund = refund_deposit(deps.branch(), env, &proposal)?;
+
+    Ok(Response::new()
+        .add_messages(refund)
+        .add_attribute("action", "approve_proposal")
+        .add_attribute("proposal_id", proposal.proposal_id.to_string())
+        .add_attribute("allocation_id", allocation_id.to_string()))
+}
+
+pub fn approve_proposal(
+    mut deps: DepsMut,
+    env: Env,
+    proposal_id: u32,
+) -> Result<Response, ContractError> {
+    let proposal = load_pending(deps.storage, proposal_id)?;
+    let mut state = STATE.load(deps.storage)?;
+    let response = approve(&mut deps, &env, &mut state, proposal)?;
+    STATE.save(deps.storage, &state)?;
+    Ok(response)
+}
+
+/// Reject a proposal, refunding the deposit or, if slashed, adding it to the registration reward pool
+pub fn reject_proposal(
+    mut deps: DepsMut,
+    env: Env,
+    proposal_id: u32,
+    slash: bool,
+) -> Result<Response, ContractError> {
+    let mut proposal = load_pending(deps.storage, proposal_id)?;
+    proposal.status = ProposalStatus::Rejected { slashed: slash };
+    PROPOSALS.insert(deps.storage, &proposal_id, &proposal)?;
+
+    let mut messages = vec![];
+    if slash {
+        let mut state = STATE.load(deps.storage)?;
+        state.registration_reward = state.registration_reward.checked_add(proposal.deposit)
+            .map_err(|_| ContractError::Overflow("registration reward"))?;
+        STATE.save(deps.storage, &state)?;
+    } else {
+        messages.extend(refund_deposit(deps.branch(), &env, &proposal)?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "reject_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("slashed", slash.to_string()))
+}
+
+/// Back a proposal as a registered human; reaching the threshold approves it
+pub fn endorse_proposal(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let registration = REGISTRATIONS.get_by_address(deps.storage, &info.sender)?
+        .ok_or(ContractError::NotRegistered)?;
+    let registration_age = env.block.time.seconds() - registration.registration_timestamp.seconds();
+    if registration_age > config.registration_validity_seconds {
+        return Err(ContractError::RegistrationExpired);
+    }
+
+    let mut proposal = load_pending(deps.storage, proposal_id)?;
+    let endorsements = PROPOSAL_ENDORSEMENTS.add_suffix(&proposal_id.to_be_bytes());
+    if endorsements.contains(deps.storage, &info.sender) {
+        return Err(ContractError::AlreadyEndorsed);
+    }
+    endorsements.insert(deps.storage, &info.sender, &true)?;
+    proposal.endorsements += 1;
+
+    let threshold = PROPOSAL_CONFIG.may_load(deps.storage)?
+        .map(|proposal_config| proposal_config.endorsement_threshold)
+        .unwrap_or(0);
+    if threshold > 0 && proposal.endorsements >= threshold {
+        let mut state = STATE.load(deps.storage)?;
+        let response = approve(&mut deps, &env, &mut state, proposal)?;
+        STATE.save(deps.storage, &state)?;
+        return Ok(response.add_attribute("endorsements", threshold.to_string()));
+    }
+
+    PROPOSALS.insert(deps.storage, &proposal_id, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "endorse_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("endorsements", proposal.endorsements.to_string()))
+}
+
+pub fn set_proposal_config(
+    deps: DepsMut,
+    proposal_config: ProposalConfig,
+) -> Result<Response, ContractError> {
+    if proposal_config.min_deposit.is_zero() {
+        return Err(ContractError::ZeroProposalDeposit);
+    }
+    // A handful of endorsements must not stand in for the timelocked ApproveProposal
+    let threshold = proposal_config.endorsement_threshold;
+    if threshold != 0 && threshold < MIN_ENDORSEMENT_THRESHOLD {
+        return Err(ContractError::EndorsementThresholdTooLow { min: MIN_ENDORSEMENT_THRESHOLD });
+    }
+
+    PROPOSAL_CONFIG.save(deps.storage, &proposal_config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_proposal_config")
+        .add_attribute("min_deposit", proposal_config.min_deposit.to_string())
+        .add_attribute("endorsement_threshold", threshold.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Uint128;
+    use crate::msg::ExecuteMsg;
+    use crate::state::{ProposalConfig, MIN_ENDORSEMENT_THRESHOLD};
+    use crate::error::ContractError;
+    use crate::testing::{setup, exec, MANAGER};
+
+    fn set_config(min_deposit: u128, endorsement_threshold: u32) -> Result<(), ContractError> {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetProposalConfig {
+            config: ProposalConfig { min_deposit: Uint128::new(min_deposit), endorsement_threshold },
+        }).map(|_| ())
+    }
+
+    #[test]
+    fn proposal_config_is_validated() {
+        assert_eq!(set_config(0, 0), Err(ContractError::ZeroProposalDeposit));
+        assert_eq!(set_config(1_000, 1), Err(ContractError::EndorsementThresholdTooLow { min: MIN_ENDORSEMENT_THRESHOLD }));
+        assert_eq!(set_config(1_000, 0), Ok(()));
+        assert_eq!(set_config(1_000, MIN_ENDORSEMENT_THRESHOLD), Ok(()));
+    }
+}
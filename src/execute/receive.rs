@@ -1,58 +1,139 @@
 // src/execute/receive.rs
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, Addr,
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128, Addr,
     Binary, from_binary};
-use crate::state::{CONFIG, STATE, query_registry};
+use crate::state::{STATE, ALLOCATION_OPTIONS, ALLOCATION_BONUS_POOL, PauseScope};
 use crate::msg::ReceiveMsg;
+use crate::execute::{proposal, reward_stream};
+use crate::execute::registry::resolve_contracts;
+use crate::execute::pause::ensure_not_paused;
+use crate::error::ContractError;
 
 pub fn receive(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     _sender: Addr,
-    _from: Addr,
+    from: Addr,
     amount: Uint128,
     msg: Binary,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
 
     let msg: ReceiveMsg = from_binary(&msg)?;
 
     match msg {
         ReceiveMsg::AllocationSend { allocation_id } =>
-            receive_allocation(deps, env, info, amount, allocation_id),
+            receive_allocation(deps, env, info, from, amount, allocation_id),
+        ReceiveMsg::ProposeAllocation(proposal) => {
+            ensure_not_paused(deps.storage, PauseScope::AllocationVoting)?;
+            proposal::propose_allocation(deps, env, info, from, amount, proposal)
+        }
+        ReceiveMsg::FundRegistrationPool {} =>
+            fund_registration_pool(deps, env, info, amount),
+        ReceiveMsg::FundRewardStream { duration_seconds } =>
+            reward_stream::fund_reward_stream(deps, env, info, amount, duration_seconds),
     }
 }
 
+/// Ensure the Receive hook was called by the ERTH token
+pub fn verify_erth_sender(deps: DepsMut, env: &Env, info: &MessageInfo) -> Result<(), ContractError> {
+    let contracts = resolve_contracts(deps, env.block.time, &["erth_token"])?;
+    if info.sender != contracts[0].address {
+        return Err(ContractError::InvalidTokenSender);
+    }
+    Ok(())
+}
+
+/// Credit an allocation's bonus pool, paid out with its next claim. Payouts this contract
+/// sends itself (the registration self-claim) refill the registration reward instead.
 fn receive_allocation(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
+    from: Addr,
     amount: Uint128,
-    _allocation_id: u32,
-) -> StdResult<Response> {
+    allocation_id: u32,
+) -> Result<Response, ContractError> {
 
-    // Load the state
-    let config = CONFIG.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
+    // Verify the deposit is ERTH
+    verify_erth_sender(deps.branch(), &env, &info)?;
 
-    // Query registry for erth_token to verify sender
-    let deps_ref = deps.as_ref();
-    let contracts = query_registry(
-        &deps_ref,
-        &config.registry_contract,
-        &config.registry_hash,
-        vec!["erth_token"],
-    )?;
-    let erth_token = &contracts[0];
-
-    if info.sender != erth_token.address {
-        return Err(StdError::generic_err("Invalid token sender"));
+    if from == env.contract.address {
+        credit_registration_pool(deps, amount)?;
+        return Ok(Response::new()
+            .add_attribute("action", "receive_allocation")
+            .add_attribute("allocation_id", allocation_id.to_string())
+            .add_attribute("registration_reward", amount.to_string()));
     }
 
-    state.registration_reward = state.registration_reward.checked_add(amount)
-        .map_err(|_| StdError::generic_err("Overflow in registration reward"))?;
-    STATE.save(deps.storage, &state)?;
+    if !ALLOCATION_OPTIONS.contains(deps.storage, &allocation_id) {
+        return Err(ContractError::AllocationNotFound);
+    }
+
+    let bonus = ALLOCATION_BONUS_POOL.get(deps.storage, &allocation_id).unwrap_or_default()
+        .checked_add(amount)
+        .map_err(|_| ContractError::Overflow("allocation bonus pool"))?;
+    ALLOCATION_BONUS_POOL.insert(deps.storage, &allocation_id, &bonus)?;
 
     Ok(Response::new()
             .add_attribute("action", "receive_allocation")
+            .add_attribute("allocation_id", allocation_id.to_string())
             .add_attribute("amount", amount.to_string()))
 }
+
+fn fund_registration_pool(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+
+    // Verify the deposit is ERTH
+    verify_erth_sender(deps.branch(), &env, &info)?;
+
+    credit_registration_pool(deps, amount)?;
+
+    Ok(Response::new()
+            .add_attribute("action", "fund_registration_pool")
+            .add_attribute("amount", amount.to_string()))
+}
+
+fn credit_registration_pool(deps: DepsMut, amount: Uint128) -> Result<(), ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    state.registration_reward = state.registration_reward.checked_add(amount)
+        .map_err(|_| ContractError::Overflow("registration reward"))?;
+    STATE.save(deps.storage, &state)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::Uint128;
+    use crate::msg::ReceiveMsg;
+    use crate::state::{STATE, ALLOCATION_BONUS_POOL};
+    use crate::testing::{setup, register, receive_erth};
+
+    fn registration_reward(deps: &crate::testing::TestDeps) -> Uint128 {
+        STATE.load(deps.as_ref().storage).unwrap().registration_reward
+    }
+
+    #[test]
+    fn self_claim_payout_refills_registration_reward() {
+        let mut deps = setup();
+        receive_erth(&mut deps, mock_env(), "sponsor", 10_000, &ReceiveMsg::FundRegistrationPool {}).unwrap();
+
+        // Each registration pays out 1% of the pool and self-claims allocation 1
+        let response = register(&mut deps, mock_env(), "alice");
+        assert_eq!(response.messages.len(), 3);
+        assert_eq!(registration_reward(&deps), Uint128::new(9_900));
+
+        // The allocation 1 payout comes back through the AllocationSend hook
+        receive_erth(&mut deps, mock_env(), MOCK_CONTRACT_ADDR, 500, &ReceiveMsg::AllocationSend { allocation_id: 1 })
+            .unwrap();
+        assert_eq!(registration_reward(&deps), Uint128::new(10_400));
+        assert!(ALLOCATION_BONUS_POOL.get(deps.as_ref().storage, &1).is_none());
+
+        register(&mut deps, mock_env(), "bob");
+        assert_eq!(registration_reward(&deps), Uint128::new(10_296));
+    }
+}
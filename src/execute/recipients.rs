@@ -0,0 +1,182 @@
+// src/execute/recipients.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Storage, to_binary,
+    CosmosMsg, WasmMsg};
+use secret_toolkit::snip20::HandleMsg;
+use crate::state::{ALLOCATION_OPTIONS, Allocation, AllocationRecipient, ALLOCATION_RECIPIENTS,
+    MAX_ALLOCATION_RECIPIENTS, ContractInfo,
+    PendingRecipientsChange, PENDING_RECIPIENTS_CHANGES, PAYOUT_CHANGE_DELAY_SECONDS};
+use crate::msg::SendMsg;
+use crate::execute::allocation_changes::check_allocation_manager;
+use crate::execute::validation::validate_recipient;
+use crate::error::ContractError;
+
+/// Payees of an allocation, falling back to its single `receive_addr`
+pub fn allocation_recipients(storage: &dyn Storage, allocation: &Allocation) -> Vec<AllocationRecipient> {
+    match ALLOCATION_RECIPIENTS.get(storage, &allocation.state.allocation_id) {
+        Some(recipients) if !recipients.is_empty() => recipients,
+        _ => vec![AllocationRecipient {
+            addr: allocation.config.receive_addr.clone(),
+            code_hash: allocation.config.receive_hash.clone(),
+            use_send: allocation.config.use_send,
+            weight: 1,
+        }],
+    }
+}
+
+/// Divide `amount` by recipient weight; the rounding remainder goes to the first recipient
+pub fn split_amount(recipients: &[AllocationRecipient], amount: Uint128) -> Vec<Uint128> {
+    let total_weight: u128 = recipients.iter().map(|recipient| recipient.weight as u128).sum();
+    let mut shares: Vec<Uint128> = recipients.iter()
+        .map(|recipient| amount.multiply_ratio(recipient.weight as u128, total_weight))
+        .collect();
+    let distributed: Uint128 = shares.iter().copied().sum();
+    if let Some(first) = shares.first_mut() {
+        *first += amount - distributed;
+    }
+    shares
+}
+
+/// Send a token share held by this contract to a `use_send` recipient, triggering its `AllocationSend` hook
+fn send_share(
+    token: &ContractInfo,
+    allocation_id: u32,
+    recipient: &AllocationRecipient,
+    share: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    let receive_hash = recipient.code_hash.clone()
+        .ok_or(ContractError::MissingRecipientCodeHash)?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.address.to_string(),
+        code_hash: token.code_hash.clone(),
+        msg: to_binary(&HandleMsg::Send {
+            recipient: recipient.addr.to_string(),
+            recipient_code_hash: Some(receive_hash),
+            amount: share,
+            msg: Some(to_binary(&SendMsg::AllocationSend { allocation_id })?),
+            memo: None,
+            padding: None,
+        })?,
+        funds: vec![],
+    }))
+}
+
+pub fn mint_msg(erth_token: &ContractInfo, recipient: String, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: erth_token.address.to_string(),
+        code_hash: erth_token.code_hash.clone(),
+        msg: to_binary(&HandleMsg::Mint {
+            recipient,
+            amount,
+            padding: None,
+            memo: None,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// Mint (and for `use_send` recipients, forward with an `AllocationSend` hook) each recipient's share
+pub fn mint_to_recipients(
+    env: &Env,
+    erth_token: &ContractInfo,
+    allocation_id: u32,
+    recipients: &[AllocationRecipient],
+    amount: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut messages = Vec::new();
+
+    for (recipient, share) in recipients.iter().zip(split_amount(recipients, amount)) {
+        if share.is_zero() {
+            continue;
+        }
+
+        if recipient.use_send {
+            // Mint to this contract, then send on to trigger the recipient's receive function
+            messages.push(mint_msg(erth_token, env.contract.address.to_string(), share)?);
+            messages.push(send_share(erth_token, allocation_id, recipient, share)?);
+        } else {
+            messages.push(mint_msg(erth_token, recipient.addr.to_string(), share)?);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Pay each recipient's share of tokens already held by this contract
+pub fn transfer_to_recipients(
+    token: &ContractInfo,
+    allocation_id: u32,
+    recipients: &[AllocationRecipient],
+    amount: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut messages = Vec::new();
+
+    for (recipient, share) in recipients.iter().zip(split_amount(recipients, amount)) {
+        if share.is_zero() {
+            continue;
+        }
+
+        if recipient.use_send {
+            messages.push(send_share(token, allocation_id, recipient, share)?);
+        } else {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token.address.to_string(),
+                code_hash: token.code_hash.clone(),
+                msg: to_binary(&HandleMsg::Transfer {
+                    recipient: recipient.addr.to_string(),
+                    amount: share,
+                    memo: None,
+                    padding: None,
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Replace the weighted recipient list of an allocation once `PAYOUT_CHANGE_DELAY_SECONDS` has
+/// passed; an empty list reverts to `receive_addr`
+pub fn set_allocation_recipients(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allocation_id: u32,
+    recipients: Vec<AllocationRecipient>,
+) -> Result<Response, ContractError> {
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+    check_allocation_manager(deps.storage, &allocation, &info.sender)?;
+
+    if recipients.len() > MAX_ALLOCATION_RECIPIENTS {
+        return Err(ContractError::TooManyRecipients { max: MAX_ALLOCATION_RECIPIENTS });
+    }
+    for recipient in &recipients {
+        if recipient.weight == 0 {
+            return Err(ContractError::ZeroRecipientWeight);
+        }
+        validate_recipient(deps.as_ref(), recipient)?;
+    }
+
+    // Setting the current list again cancels a pending change
+    let current = ALLOCATION_RECIPIENTS.get(deps.storage, &allocation_id).unwrap_or_default();
+    let mut response = Response::new()
+        .add_attribute("action", "set_allocation_recipients")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("recipients", recipients.len().to_string());
+    if recipients == current {
+        if PENDING_RECIPIENTS_CHANGES.contains(deps.storage, &allocation_id) {
+            PENDING_RECIPIENTS_CHANGES.remove(deps.storage, &allocation_id)?;
+        }
+    } else {
+        let effective_at = env.block.time.plus_seconds(PAYOUT_CHANGE_DELAY_SECONDS);
+        PENDING_RECIPIENTS_CHANGES.insert(deps.storage, &allocation_id, &PendingRecipientsChange {
+            recipients,
+            effective_at,
+        })?;
+        response = response.add_attribute("effective_at", effective_at.seconds().to_string());
+    }
+
+    Ok(response)
+}
@@ -1,27 +1,25 @@
 use cosmwasm_std::{
-    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, Timestamp, CosmosMsg, WasmMsg,
+    DepsMut, Env, Response, Uint128, Timestamp, CosmosMsg, WasmMsg,
     to_binary,
 };
 use secret_toolkit::snip20::{self, HandleMsg};
-use crate::state::{CONFIG, STATE, REGISTRATIONS, Registration, NEW_REGISTRATIONS_COUNT, query_registry};
+use crate::state::{CONFIG, STATE, REGISTRATIONS, Registration, NEW_REGISTRATIONS_COUNT, PauseScope};
 use crate::msg::ExecuteMsg;
+use crate::execute::allocation::sync_allocations;
+use crate::execute::registry::resolve_contracts;
+use crate::execute::pause::is_paused;
+use crate::error::ContractError;
 
 pub fn register(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    info: MessageInfo,
     address: String,
     id_hash: String,
     affiliate: Option<String>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
 
-    // Check if the sender is authorized
-    if info.sender != config.registration_address {
-        return Err(StdError::generic_err("Not authorized"));
-    }
-
     // Validate the registree address
     let wallet_address_addr = deps.api.addr_validate(&address)?;
 
@@ -31,7 +29,7 @@ pub fn register(
         // Calculate expiration time
         let expiration = reg.registration_timestamp.plus_seconds(config.registration_validity_seconds);
         if env.block.time <= expiration {
-            return Err(StdError::generic_err("Registration still valid, cannot re-register yet"));
+            return Err(ContractError::RegistrationStillValid);
         }
         // If expired, remove the old registration to allow re-registration
         REGISTRATIONS.remove(deps.storage, &wallet_address_addr, &reg.id_hash)?;
@@ -45,7 +43,7 @@ pub fn register(
         if env.block.time > expiration {
             REGISTRATIONS.remove(deps.storage, &existing_reg.address, &id_hash)?;
         } else {
-            return Err(StdError::generic_err("ID hash already registered and not expired"));
+            return Err(ContractError::IdHashRegistered);
         }
     }
 
@@ -69,14 +67,11 @@ pub fn register(
     // Increment registration count
     state.registrations += 1;
 
+    // Drop lapsed votes while state is being written anyway
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+
     // Query registry for contract references
-    let deps_ref = deps.as_ref();
-    let contracts = query_registry(
-        &deps_ref,
-        &config.registry_contract,
-        &config.registry_hash,
-        vec!["erth_token", "anml_token"],
-    )?;
+    let contracts = resolve_contracts(deps.branch(), env.block.time, &["erth_token", "anml_token"])?;
     let erth_token = &contracts[0];
     let anml_token = &contracts[1];
 
@@ -131,7 +126,6 @@ pub fn register(
         }
     }
 
-
      // Create message for minting ANML to the user
     let mint_anml = HandleMsg::Mint {
         recipient: address.clone(),
@@ -146,16 +140,18 @@ pub fn register(
         funds: vec![],
     }));
 
-
-    // Execute claim_allocation on self
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: env.contract.address.to_string(),
-        code_hash: env.contract.code_hash.clone(),
-        msg: to_binary(&ExecuteMsg::ClaimAllocation {
-            allocation_id: 1,
-        })?,
-        funds: vec![],
-    }));
+    // Execute claim_allocation on self, unless allocation claims are paused; the claim
+    // would be rejected and take the registration down with it
+    if !is_paused(deps.storage, PauseScope::AllocationClaims)? {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            code_hash: env.contract.code_hash.clone(),
+            msg: to_binary(&ExecuteMsg::ClaimAllocation {
+                allocation_id: 1,
+            })?,
+            funds: vec![],
+        }));
+    }
 
     // Update state after successful registration
     STATE.save(deps.storage, &state)?;
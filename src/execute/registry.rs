@@ -0,0 +1,143 @@
+// src/execute/registry.rs
+use cosmwasm_std::{Deps, DepsMut, Env, Response, StdResult, Storage, Timestamp};
+use crate::state::{CONFIG, ContractInfo, CachedContract, REGISTRY_CONTRACT_NAMES, REGISTRY_CACHE_TTL_SECONDS,
+    REGISTRY_CACHE, REGISTRY_PINS, query_registry};
+use crate::error::ContractError;
+
+/// Registry answers to write back into the cache, by name
+pub type FetchedContracts = Vec<(String, ContractInfo)>;
+
+fn is_fresh(cached: &CachedContract, now: Timestamp) -> bool {
+    now.seconds().saturating_sub(cached.fetched_at.seconds()) < REGISTRY_CACHE_TTL_SECONDS
+}
+
+/// Resolve registry contracts without writing to storage: pinned overrides first, then fresh
+/// cache entries, then the registry. If the registry query fails, stale cache entries are used
+/// instead. Returns the contracts in `names` order along with anything fetched from the registry.
+pub fn lookup_contracts(
+    deps: Deps,
+    now: Timestamp,
+    names: &[&str],
+) -> StdResult<(Vec<ContractInfo>, FetchedContracts)> {
+    let mut resolved = Vec::with_capacity(names.len());
+    let mut missing = vec![];
+    for (index, name) in names.iter().enumerate() {
+        let key = name.to_string();
+        if let Some(pinned) = REGISTRY_PINS.get(deps.storage, &key) {
+            resolved.push(Some(pinned));
+            continue;
+        }
+        match REGISTRY_CACHE.get(deps.storage, &key) {
+            Some(cached) if is_fresh(&cached, now) => resolved.push(Some(cached.info)),
+            _ => {
+                resolved.push(None);
+                missing.push(index);
+            }
+        }
+    }
+
+    let mut fetched = vec![];
+    if !missing.is_empty() {
+        let config = CONFIG.load(deps.storage)?;
+        let missing_names = missing.iter().map(|index| names[*index]).collect();
+        match query_registry(&deps, &config.registry_contract, &config.registry_hash, missing_names) {
+            Ok(infos) => {
+                for (index, info) in missing.iter().zip(infos) {
+                    fetched.push((names[*index].to_string(), info.clone()));
+                    resolved[*index] = Some(info);
+                }
+            }
+            Err(err) => {
+                // A misbehaving registry shouldn't halt the contract while we still know the answer
+                for index in &missing {
+                    match REGISTRY_CACHE.get(deps.storage, &names[*index].to_string()) {
+                        Some(stale) => resolved[*index] = Some(stale.info),
+                        None => return Err(err),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((resolved.into_iter().flatten().collect(), fetched))
+}
+
+/// Resolve registry contracts, caching whatever had to be fetched from the registry
+pub fn resolve_contracts(deps: DepsMut, now: Timestamp, names: &[&str]) -> StdResult<Vec<ContractInfo>> {
+    let (contracts, fetched) = lookup_contracts(deps.as_ref(), now, names)?;
+    for (name, info) in fetched {
+        REGISTRY_CACHE.insert(deps.storage, &name, &CachedContract { info, fetched_at: now })?;
+    }
+    Ok(contracts)
+}
+
+/// Drop every cached lookup, e.g. after the registry itself changes
+pub fn clear_registry_cache(storage: &mut dyn Storage) -> StdResult<()> {
+    for name in REGISTRY_CONTRACT_NAMES {
+        let key = name.to_string();
+        if REGISTRY_CACHE.contains(storage, &key) {
+            REGISTRY_CACHE.remove(storage, &key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-query every unpinned contract from the registry, failing if the registry does
+pub fn refresh_registry(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let names: Vec<&str> = REGISTRY_CONTRACT_NAMES.iter()
+        .copied()
+        .filter(|name| !REGISTRY_PINS.contains(deps.storage, &name.to_string()))
+        .collect();
+
+    let mut response = Response::new()
+        .add_attribute("action", "refresh_registry");
+    if names.is_empty() {
+        return Ok(response);
+    }
+
+    let contracts = query_registry(&deps.as_ref(), &config.registry_contract, &config.registry_hash, names.clone())?;
+    for (name, info) in names.iter().zip(contracts) {
+        response = response.add_attribute(*name, info.address.to_string());
+        REGISTRY_CACHE.insert(deps.storage, &name.to_string(), &CachedContract { info, fetched_at: env.block.time })?;
+    }
+
+    Ok(response)
+}
+
+/// Pin a contract to a fixed address and code hash, or remove the pin with `None`
+pub fn set_registry_pin(
+    deps: DepsMut,
+    name: String,
+    contract: Option<ContractInfo>,
+) -> Result<Response, ContractError> {
+    if !REGISTRY_CONTRACT_NAMES.contains(&name.as_str()) {
+        return Err(ContractError::UnknownRegistryName { name });
+    }
+
+    let response = Response::new()
+        .add_attribute("action", "set_registry_pin")
+        .add_attribute("name", name.clone());
+
+    match contract {
+        Some(contract) => {
+            let address = deps.api.addr_validate(contract.address.as_str())?;
+            if contract.code_hash.len() != 64 || !contract.code_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ContractError::InvalidPinnedCodeHash);
+            }
+            let pinned = ContractInfo { address, code_hash: contract.code_hash };
+            REGISTRY_PINS.insert(deps.storage, &name, &pinned)?;
+            Ok(response.add_attribute("address", pinned.address.to_string()))
+        }
+        None => {
+            if !REGISTRY_PINS.contains(deps.storage, &name) {
+                return Err(ContractError::RegistryNameNotPinned { name });
+            }
+            REGISTRY_PINS.remove(deps.storage, &name)?;
+            Ok(response.add_attribute("address", "none"))
+        }
+    }
+}
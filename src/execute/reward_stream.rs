@@ -0,0 +1,407 @@
+// src/execute/reward_stream.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Addr, Storage, Timestamp,
+    to_binary, CosmosMsg, WasmMsg};
+use secret_toolkit::snip20::HandleMsg;
+use crate::state::{STATE, ALLOCATION_OPTIONS, ContractInfo, RewardStream, AllocationTokenReward, VoterReward,
+    VoterTokenReward, MAX_REWARD_TOKENS, REWARD_TOKENS, REWARD_STREAMS, ALLOCATION_TOKEN_REWARDS,
+    VOTER_TOKEN_REWARDS, EPOCH_VOTER_TOKEN_INDEX, VOTER_REWARD_POOL, VOTER_REWARDS};
+use crate::execute::allocation::{sync_allocations, INDEX_PRECISION};
+use crate::execute::voter_reward::voter_reward_bps;
+use crate::execute::recipients::{allocation_recipients, transfer_to_recipients};
+use crate::execute::allocation_changes::apply_pending_payout;
+use crate::error::ContractError;
+
+const MIN_STREAM_DURATION: u64 = 86400;
+
+/// Stream the share of `remaining` due between the last update and `now` into the indexes,
+/// `voter_bps` of it to voters by `voter_weight` (all of it to allocations while nobody votes).
+/// While nothing is allocated the stream is paused by pushing its end back.
+pub fn update_stream_index(
+    stream: &mut RewardStream,
+    total_allocations: Uint128,
+    voter_weight: Uint128,
+    voter_bps: u16,
+    now: Timestamp,
+) {
+    if now <= stream.last_update {
+        return;
+    }
+    if stream.remaining.is_zero() || stream.stream_end <= stream.last_update {
+        stream.last_update = now;
+        return;
+    }
+    if total_allocations.is_zero() {
+        stream.stream_end = stream.stream_end.plus_seconds(now.seconds() - stream.last_update.seconds());
+        stream.last_update = now;
+        return;
+    }
+
+    let until = now.min(stream.stream_end);
+    let streamed = stream.remaining.multiply_ratio(
+        until.seconds() - stream.last_update.seconds(),
+        stream.stream_end.seconds() - stream.last_update.seconds(),
+    );
+    stream.remaining -= streamed;
+    let mut voter_share = Uint128::zero();
+    if !voter_weight.is_zero() {
+        voter_share = streamed.multiply_ratio(voter_bps as u128, 10_000u128);
+        stream.voter_reward_index += voter_share.multiply_ratio(INDEX_PRECISION, voter_weight);
+    }
+    stream.reward_index += (streamed - voter_share).multiply_ratio(INDEX_PRECISION, total_allocations);
+    stream.last_update = now;
+}
+
+/// Bring every reward stream up to `now`
+pub fn update_stream_indexes(storage: &mut dyn Storage, total_allocations: Uint128, now: Timestamp) -> StdResult<()> {
+    let voter_weight = VOTER_REWARD_POOL.may_load(storage)?.unwrap_or_default().total_weight;
+    let voter_bps = voter_reward_bps(storage);
+    for token in REWARD_TOKENS.may_load(storage)?.unwrap_or_default() {
+        if let Some(mut stream) = REWARD_STREAMS.get(storage, &token) {
+            update_stream_index(&mut stream, total_allocations, voter_weight, voter_bps, now);
+            REWARD_STREAMS.insert(storage, &token, &stream)?;
+        }
+    }
+    Ok(())
+}
+
+/// Credit an allocation's pending stream rewards to its per-token balances.
+/// Must run before `amount_allocated` changes, like the ERTH settlement.
+pub fn settle_token_rewards(storage: &mut dyn Storage, allocation_id: u32, amount_allocated: Uint128) -> StdResult<()> {
+    for token in REWARD_TOKENS.may_load(storage)?.unwrap_or_default() {
+        let stream = match REWARD_STREAMS.get(storage, &token) {
+            Some(stream) => stream,
+            None => continue,
+        };
+        let rewards = ALLOCATION_TOKEN_REWARDS.add_suffix(&allocation_id.to_be_bytes());
+        let mut position = rewards.get(storage, &token).unwrap_or_default();
+        if position.last_reward_index == stream.reward_index {
+            continue;
+        }
+        position.accumulated_rewards += amount_allocated.multiply_ratio(
+            stream.reward_index - position.last_reward_index,
+            INDEX_PRECISION,
+        );
+        position.last_reward_index = stream.reward_index;
+        rewards.insert(storage, &token, &position)?;
+    }
+    Ok(())
+}
+
+/// Pending rewards of an allocation in one stream, given an up-to-date stream
+pub fn pending_token_rewards(storage: &dyn Storage, allocation_id: u32, amount_allocated: Uint128, stream: &RewardStream) -> Uint128 {
+    let position: AllocationTokenReward = ALLOCATION_TOKEN_REWARDS.add_suffix(&allocation_id.to_be_bytes())
+        .get(storage, &stream.token)
+        .unwrap_or_default();
+    position.accumulated_rewards + amount_allocated.multiply_ratio(
+        stream.reward_index - position.last_reward_index,
+        INDEX_PRECISION,
+    )
+}
+
+/// A voter's stream position with what `voter` (as stored before settling) has earned since
+/// its last settlement. Weight from a past epoch only earns up to that epoch's reset.
+fn settle_voter_position(storage: &dyn Storage, address: &Addr, voter: &VoterReward, epoch: u32, stream: &RewardStream) -> VoterTokenReward {
+    let mut position = VOTER_TOKEN_REWARDS.add_suffix(address.as_bytes())
+        .get(storage, &stream.token)
+        .unwrap_or_default();
+    let index = if voter.epoch == epoch {
+        stream.voter_reward_index
+    } else {
+        EPOCH_VOTER_TOKEN_INDEX.add_suffix(stream.token.as_bytes())
+            .get(storage, &voter.epoch)
+            .unwrap_or(position.last_reward_index)
+    };
+    position.accumulated_rewards += voter.weight.multiply_ratio(
+        index.saturating_sub(position.last_reward_index),
+        INDEX_PRECISION,
+    );
+    position.last_reward_index = stream.voter_reward_index;
+    position
+}
+
+/// Credit a voter's pending stream rewards to its per-token balances.
+/// Must run before the voter's weight changes, like the ERTH voter settlement.
+pub fn settle_voter_token_rewards(storage: &mut dyn Storage, address: &Addr, epoch: u32) -> StdResult<()> {
+    let voter = VOTER_REWARDS.get(storage, address).unwrap_or_default();
+    for token in REWARD_TOKENS.may_load(storage)?.unwrap_or_default() {
+        if let Some(stream) = REWARD_STREAMS.get(storage, &token) {
+            let position = settle_voter_position(storage, address, &voter, epoch, &stream);
+            VOTER_TOKEN_REWARDS.add_suffix(address.as_bytes()).insert(storage, &token, &position)?;
+        }
+    }
+    Ok(())
+}
+
+/// Pending rewards of a voter in one stream, given an up-to-date stream
+pub fn pending_voter_token_rewards(storage: &dyn Storage, address: &Addr, epoch: u32, stream: &RewardStream) -> Uint128 {
+    let voter = VOTER_REWARDS.get(storage, address).unwrap_or_default();
+    settle_voter_position(storage, address, &voter, epoch, stream).accumulated_rewards
+}
+
+/// Freeze every stream's voter index for the closing epoch
+pub fn close_voter_token_epoch(storage: &mut dyn Storage, epoch: u32) -> StdResult<()> {
+    for stream in reward_streams(storage)? {
+        EPOCH_VOTER_TOKEN_INDEX.add_suffix(stream.token.as_bytes())
+            .insert(storage, &epoch, &stream.voter_reward_index)?;
+    }
+    Ok(())
+}
+
+/// Accept deposits of a partner token and register this contract as its receiver
+pub fn register_reward_token(
+    deps: DepsMut,
+    env: Env,
+    token: String,
+    code_hash: String,
+) -> Result<Response, ContractError> {
+    let token = deps.api.addr_validate(&token)?;
+    let mut tokens = REWARD_TOKENS.may_load(deps.storage)?.unwrap_or_default();
+    if tokens.contains(&token) {
+        return Err(ContractError::RewardTokenRegistered);
+    }
+    if tokens.len() >= MAX_REWARD_TOKENS {
+        return Err(ContractError::TooManyRewardTokens { max: MAX_REWARD_TOKENS });
+    }
+    tokens.push(token.clone());
+    REWARD_TOKENS.save(deps.storage, &tokens)?;
+
+    REWARD_STREAMS.insert(deps.storage, &token, &RewardStream {
+        token: token.clone(),
+        code_hash: code_hash.clone(),
+        reward_index: Uint128::zero(),
+        voter_reward_index: Uint128::zero(),
+        remaining: Uint128::zero(),
+        stream_end: env.block.time,
+        last_update: env.block.time,
+    })?;
+
+    let register_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        code_hash,
+        msg: to_binary(&HandleMsg::RegisterReceive {
+            code_hash: env.contract.code_hash.clone(),
+            padding: None,
+        })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(register_msg)
+        .add_attribute("action", "register_reward_token")
+        .add_attribute("token", token.to_string()))
+}
+
+/// Receive hook for a registered partner token: spread the deposit, together with whatever
+/// is still streaming, until the later of the current end and `duration_seconds` from now
+pub fn fund_reward_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    duration_seconds: u64,
+) -> Result<Response, ContractError> {
+    if duration_seconds < MIN_STREAM_DURATION {
+        return Err(ContractError::StreamTooShort { min: MIN_STREAM_DURATION });
+    }
+    if !REWARD_STREAMS.contains(deps.storage, &info.sender) {
+        return Err(ContractError::UnknownRewardToken);
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    let mut stream = REWARD_STREAMS.get(deps.storage, &info.sender)
+        .ok_or(ContractError::UnknownRewardToken)?;
+    stream.remaining = stream.remaining.checked_add(amount)
+        .map_err(|_| ContractError::Overflow("reward stream"))?;
+    stream.stream_end = stream.stream_end.max(env.block.time.plus_seconds(duration_seconds));
+    REWARD_STREAMS.insert(deps.storage, &info.sender, &stream)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_reward_stream")
+        .add_attribute("token", info.sender.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("stream_end", stream.stream_end.seconds().to_string()))
+}
+
+/// Pay an allocation's rewards in a partner token to its current recipients.
+/// Partner tokens are paid out directly, without vesting.
+pub fn claim_allocation_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allocation_id: u32,
+    token: String,
+) -> Result<Response, ContractError> {
+    let token = deps.api.addr_validate(&token)?;
+    let stream = REWARD_STREAMS.get(deps.storage, &token)
+        .ok_or(ContractError::UnknownRewardToken)?;
+
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+
+    // Same restriction as claiming ERTH
+    if let Some(claimer_addr) = &allocation.config.claimer_addr {
+        if &info.sender != claimer_addr {
+            return Err(ContractError::NotAllocationClaimer);
+        }
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    // Expiries processed above may have changed the allocation
+    let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+    if apply_pending_payout(deps.storage, &mut allocation, env.block.time)? {
+        ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
+    }
+    settle_token_rewards(deps.storage, allocation_id, allocation.state.amount_allocated)?;
+
+    let rewards = ALLOCATION_TOKEN_REWARDS.add_suffix(&allocation_id.to_be_bytes());
+    let mut position = rewards.get(deps.storage, &token).unwrap_or_default();
+    let amount = position.accumulated_rewards;
+    let mut messages = vec![];
+    if !amount.is_zero() {
+        position.accumulated_rewards = Uint128::zero();
+        rewards.insert(deps.storage, &token, &position)?;
+
+        let token_info = ContractInfo {
+            address: stream.token.clone(),
+            code_hash: stream.code_hash.clone(),
+        };
+        let recipients = allocation_recipients(deps.storage, &allocation);
+        messages = transfer_to_recipients(&token_info, allocation_id, &recipients, amount)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_allocation_token")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("token", token.to_string())
+        .add_attribute("amount", amount.to_string()))
+}
+
+/// Pay a voter's rewards in a partner token
+pub fn claim_voter_token_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    let token = deps.api.addr_validate(&token)?;
+    if !REWARD_STREAMS.contains(deps.storage, &token) {
+        return Err(ContractError::UnknownRewardToken);
+    }
+
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    let stream = REWARD_STREAMS.get(deps.storage, &token)
+        .ok_or(ContractError::UnknownRewardToken)?;
+    let voter = VOTER_REWARDS.get(deps.storage, &info.sender).unwrap_or_default();
+    let mut position = settle_voter_position(deps.storage, &info.sender, &voter, state.epoch, &stream);
+    let amount = position.accumulated_rewards;
+    position.accumulated_rewards = Uint128::zero();
+    VOTER_TOKEN_REWARDS.add_suffix(info.sender.as_bytes()).insert(deps.storage, &token, &position)?;
+
+    let mut messages = vec![];
+    if !amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: stream.token.to_string(),
+            code_hash: stream.code_hash.clone(),
+            msg: to_binary(&HandleMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+                memo: None,
+                padding: None,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_voter_token_rewards")
+        .add_attribute("token", token.to_string())
+        .add_attribute("amount", amount.to_string()))
+}
+
+/// Tokens streamed to allocations and voters
+pub fn reward_streams(storage: &dyn Storage) -> StdResult<Vec<RewardStream>> {
+    Ok(REWARD_TOKENS.may_load(storage)?.unwrap_or_default()
+        .iter()
+        .filter_map(|token| REWARD_STREAMS.get(storage, token))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{from_binary, to_binary, Addr, Env, Uint128};
+    use crate::msg::{ExecuteMsg, QueryMsg, ReceiveMsg, TokenRewardResponse};
+    use crate::state::{VotingConfig, VotingWeightMode, VoterRewardConfig};
+    use crate::testing::{setup, exec, register, add_allocation, vote, TestDeps, MANAGER};
+    use crate::query;
+
+    const PARTNER_TOKEN: &str = "partner_token";
+    const HALF_VALIDITY_SECONDS: u64 = 60 * 60 * 24 * 15;
+
+    fn at(seconds_later: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds_later);
+        env
+    }
+
+    fn voter_pending(deps: &TestDeps, env: Env, address: &str) -> Uint128 {
+        let rewards: Vec<TokenRewardResponse> = from_binary(&query(deps.as_ref(), env, QueryMsg::QueryVoterTokenRewards {
+            address: address.to_string(),
+        }).unwrap()).unwrap();
+        rewards[0].pending
+    }
+
+    #[test]
+    fn voters_split_a_stream_by_weight() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetVoterRewardConfig {
+            config: VoterRewardConfig { voter_reward_bps: 2_000 },
+        }).unwrap();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::UpdateVotingConfig {
+            config: VotingConfig { weight_mode: VotingWeightMode::Seniority },
+        }).unwrap();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::RegisterRewardToken {
+            token: PARTNER_TOKEN.to_string(),
+            code_hash: "partner_hash".to_string(),
+        }).unwrap();
+        let allocation_id = add_allocation(&mut deps, "project");
+
+        // Halfway through its validity alice's registration weighs 1.5x bob's fresh one
+        register(&mut deps, mock_env(), "alice");
+        let start = HALF_VALIDITY_SECONDS;
+        register(&mut deps, at(start), "bob");
+        vote(&mut deps, at(start), "alice", &[(allocation_id, 100)]).unwrap();
+        vote(&mut deps, at(start), "bob", &[(allocation_id, 100)]).unwrap();
+
+        exec(&mut deps, at(start), PARTNER_TOKEN, ExecuteMsg::Receive {
+            sender: Addr::unchecked("sponsor"),
+            from: Addr::unchecked("sponsor"),
+            amount: Uint128::new(1_000_000),
+            memo: None,
+            msg: to_binary(&ReceiveMsg::FundRewardStream { duration_seconds: 86_400 }).unwrap(),
+        }).unwrap();
+
+        // Voters share 20% of the stream 3:2
+        let end = start + 86_400;
+        let alice = voter_pending(&deps, at(end), "alice");
+        let bob = voter_pending(&deps, at(end), "bob");
+        assert_eq!(alice, Uint128::new(120_000));
+        assert_eq!(bob, Uint128::new(80_000));
+
+        let allocation: Vec<TokenRewardResponse> = from_binary(&query(deps.as_ref(), at(end),
+            QueryMsg::QueryAllocationTokenRewards { allocation_id }).unwrap()).unwrap();
+        assert_eq!(allocation[0].pending + alice + bob, Uint128::new(1_000_000));
+    }
+}
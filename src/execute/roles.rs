@@ -0,0 +1,117 @@
+// src/execute/roles.rs
+use cosmwasm_std::{DepsMut, MessageInfo, Response, StdResult, Addr, Storage};
+use crate::state::{CONFIG, Role, ROLES};
+use crate::msg::ExecuteMsg;
+use crate::error::ContractError;
+
+/// Whether `address` holds `role`, including the roles implied by the config and by Admin
+pub fn has_role(storage: &dyn Storage, address: &Addr, role: Role) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+    if *address == config.contract_manager {
+        return Ok(true);
+    }
+    if role == Role::Registrar && *address == config.registration_address {
+        return Ok(true);
+    }
+    let roles = ROLES.get(storage, address).unwrap_or_default();
+    Ok(roles.contains(&role) || roles.contains(&Role::Admin))
+}
+
+pub fn check_role(storage: &dyn Storage, address: &Addr, role: Role) -> Result<(), ContractError> {
+    if !has_role(storage, address, role)? {
+        return Err(ContractError::MissingRole { role });
+    }
+    Ok(())
+}
+
+/// Role an entry point requires. Messages returning `None` are open to everyone or check
+/// per-object permissions themselves (the contract manager, allocation managers, claimers,
+/// proposed addresses).
+pub fn required_role(msg: &ExecuteMsg) -> Option<Role> {
+    match msg {
+        ExecuteMsg::UpdateConfig(_)
+        | ExecuteMsg::UpdateVotingConfig { .. }
+        | ExecuteMsg::SetEpochVotingRules { .. }
+        | ExecuteMsg::SetProposalConfig { .. }
+        | ExecuteMsg::SetAllocationVesting { .. }
+        | ExecuteMsg::RegisterRewardToken { .. }
+        | ExecuteMsg::SetVoterRewardConfig { .. }
+        | ExecuteMsg::SetAdminTimelock { .. }
+        | ExecuteMsg::QueueAdminAction { .. }
+        | ExecuteMsg::ExecuteAdminAction { .. }
+        | ExecuteMsg::CancelAdminAction { .. }
+        | ExecuteMsg::GrantRole { .. }
+        | ExecuteMsg::RevokeRole { .. }
+        | ExecuteMsg::SetRegistryPin { .. } => Some(Role::Admin),
+
+        ExecuteMsg::Register { .. } => Some(Role::Registrar),
+
+        ExecuteMsg::AddAllocation { .. }
+        | ExecuteMsg::ResetAllocations {}
+        | ExecuteMsg::ApproveProposal { .. }
+        | ExecuteMsg::RejectProposal { .. } => Some(Role::AllocationAdmin),
+
+        ExecuteMsg::SetPause { .. } => Some(Role::Pauser),
+
+        ExecuteMsg::ProcessVoteExpiries { .. }
+        | ExecuteMsg::ProcessAutoRenewals {}
+        | ExecuteMsg::RefreshRegistry {}
+        | ExecuteMsg::RepairTotals {} => Some(Role::Keeper),
+
+        _ => None,
+    }
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // Admins can't mint new admins; only the contract manager can
+    if role == Role::Admin && info.sender != config.contract_manager {
+        return Err(ContractError::AdminRoleManagerOnly);
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let mut roles = ROLES.get(deps.storage, &address).unwrap_or_default();
+    if !roles.contains(&role) {
+        roles.push(role);
+        ROLES.insert(deps.storage, &address, &roles)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("address", address.to_string())
+        .add_attribute("role", format!("{:?}", role)))
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if role == Role::Admin && info.sender != config.contract_manager {
+        return Err(ContractError::AdminRoleManagerOnly);
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let mut roles = ROLES.get(deps.storage, &address).unwrap_or_default();
+    if !roles.contains(&role) {
+        return Err(ContractError::RoleNotHeld);
+    }
+    roles.retain(|held| *held != role);
+    if roles.is_empty() {
+        ROLES.remove(deps.storage, &address)?;
+    } else {
+        ROLES.insert(deps.storage, &address, &roles)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("address", address.to_string())
+        .add_attribute("role", format!("{:?}", role)))
+}
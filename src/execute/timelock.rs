@@ -0,0 +1,202 @@
+// src/execute/timelock.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use crate::state::{Role, QueuedAdminAction, MAX_ADMIN_TIMELOCK_SECONDS, ADMIN_TIMELOCK_SECONDS, ADMIN_ACTIONS,
+    ADMIN_ACTION_COUNTER};
+use crate::msg::AdminAction;
+use crate::execute::{update_config, allocation, proposal, roles, registry, vesting, voter_reward, reward_stream,
+    recipients, allocation_changes};
+use crate::execute::roles::has_role;
+use crate::error::ContractError;
+
+pub fn admin_timelock_seconds(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(ADMIN_TIMELOCK_SECONDS.may_load(storage)?.unwrap_or(0))
+}
+
+/// While the timelock is enabled holders of `role` have to queue sensitive actions.
+/// Allocation managers editing their own allocation are unaffected.
+///
+/// Every privileged message is guarded except these, which have to act immediately or can't
+/// change config, payouts or permissions:
+/// - `SetPause`: incident response; a pause only stops activity and moves no funds
+/// - `CancelManagerProposal`, `CancelAdminAction`: withdraw a pending change, e.g. a hijack
+/// - `QueueAdminAction`, `ExecuteAdminAction`: the queue itself; `SetAdminTimelock` checks itself
+/// - `RepairTotals`, `ProcessVoteExpiries`, `ProcessAutoRenewals`, `RefreshRegistry`: keeper
+///   upkeep that only brings derived state to what the stored data already implies
+pub fn ensure_not_timelocked(storage: &dyn Storage, info: &MessageInfo, role: Role) -> Result<(), ContractError> {
+    if admin_timelock_seconds(storage)? > 0 && has_role(storage, &info.sender, role)? {
+        return Err(ContractError::Timelocked);
+    }
+    Ok(())
+}
+
+fn apply_admin_timelock(deps: DepsMut, delay_seconds: u64) -> Result<Response, ContractError> {
+    if delay_seconds > MAX_ADMIN_TIMELOCK_SECONDS {
+        return Err(ContractError::TimelockTooLong { max: MAX_ADMIN_TIMELOCK_SECONDS });
+    }
+    ADMIN_TIMELOCK_SECONDS.save(deps.storage, &delay_seconds)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_admin_timelock")
+        .add_attribute("delay_seconds", delay_seconds.to_string()))
+}
+
+/// Enable the timelock or, while it is disabled, change it directly. Once enabled,
+/// changing or disabling it has to be queued like any other sensitive action.
+pub fn set_admin_timelock(
+    deps: DepsMut,
+    info: MessageInfo,
+    delay_seconds: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_timelocked(deps.storage, &info, Role::Admin)?;
+
+    apply_admin_timelock(deps, delay_seconds)
+}
+
+pub fn queue_admin_action(
+    deps: DepsMut,
+    env: Env,
+    action: AdminAction,
+) -> Result<Response, ContractError> {
+    let delay = admin_timelock_seconds(deps.storage)?;
+    if delay == 0 {
+        return Err(ContractError::TimelockDisabled);
+    }
+
+    let id = ADMIN_ACTION_COUNTER.may_load(deps.storage)?.unwrap_or(0) + 1;
+    ADMIN_ACTION_COUNTER.save(deps.storage, &id)?;
+
+    let eta = env.block.time.plus_seconds(delay);
+    ADMIN_ACTIONS.insert(deps.storage, &id, &QueuedAdminAction {
+        id,
+        action,
+        queued_at: env.block.time,
+        eta,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "queue_admin_action")
+        .add_attribute("id", id.to_string())
+        .add_attribute("eta", eta.seconds().to_string()))
+}
+
+/// Run a queued action once its ETA has passed. It runs with the executor's `info`, so
+/// sender checks inside the action (e.g. only the manager grants Admin) apply to the executor.
+pub fn execute_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u32,
+) -> Result<Response, ContractError> {
+    let queued = ADMIN_ACTIONS.get(deps.storage, &id)
+        .ok_or(ContractError::AdminActionNotFound)?;
+    if env.block.time < queued.eta {
+        return Err(ContractError::AdminActionNotReady { eta: queued.eta.seconds() });
+    }
+    ADMIN_ACTIONS.remove(deps.storage, &id)?;
+
+    let response = match queued.action {
+        AdminAction::UpdateConfig(update) => update_config::update_config(deps, env, update),
+        AdminAction::AddAllocation { config } => allocation::add_allocation(deps, env, config),
+        AdminAction::EditAllocation { allocation_id, config } => allocation::edit_allocation(deps, env, info, allocation_id, config),
+        AdminAction::ResetAllocations {} => allocation::reset_allocations(deps, env),
+        AdminAction::SetAdminTimelock { delay_seconds } => apply_admin_timelock(deps, delay_seconds),
+        AdminAction::ProposeManager { address } => update_config::propose_manager(deps, env, info, address),
+        AdminAction::ApproveProposal { proposal_id } => proposal::approve_proposal(deps, env, proposal_id),
+        AdminAction::GrantRole { address, role } => roles::grant_role(deps, info, address, role),
+        AdminAction::RevokeRole { address, role } => roles::revoke_role(deps, info, address, role),
+        AdminAction::SetRegistryPin { name, contract } => registry::set_registry_pin(deps, name, contract),
+        AdminAction::SetAllocationVesting { allocation_id, duration_seconds } =>
+            vesting::set_allocation_vesting(deps, allocation_id, duration_seconds),
+        AdminAction::UpdateVotingConfig { config } => update_config::update_voting_config(deps, env, config),
+        AdminAction::SetEpochVotingRules { epoch, rules } => allocation::set_epoch_voting_rules(deps, epoch, rules),
+        AdminAction::SetVoterRewardConfig { config } => voter_reward::set_voter_reward_config(deps, env, config),
+        AdminAction::RegisterRewardToken { token, code_hash } =>
+            reward_stream::register_reward_token(deps, env, token, code_hash),
+        AdminAction::SetProposalConfig { config } => proposal::set_proposal_config(deps, config),
+        AdminAction::RejectProposal { proposal_id, slash } => proposal::reject_proposal(deps, env, proposal_id, slash),
+        AdminAction::SetAllocationRecipients { allocation_id, recipients } =>
+            recipients::set_allocation_recipients(deps, env, info, allocation_id, recipients),
+        AdminAction::ProposeAllocationManager { allocation_id, manager } =>
+            allocation_changes::propose_allocation_manager(deps, info, allocation_id, manager),
+        AdminAction::ProposeAllocationClaimer { allocation_id, claimer } =>
+            allocation_changes::propose_allocation_claimer(deps, env, info, allocation_id, claimer),
+    }?;
+
+    Ok(response.add_attribute("admin_action_id", id.to_string()))
+}
+
+pub fn cancel_admin_action(
+    deps: DepsMut,
+    id: u32,
+) -> Result<Response, ContractError> {
+    if !ADMIN_ACTIONS.contains(deps.storage, &id) {
+        return Err(ContractError::AdminActionNotFound);
+    }
+
+    ADMIN_ACTIONS.remove(deps.storage, &id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_admin_action")
+        .add_attribute("id", id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Uint128;
+    use crate::msg::{AdminAction, ExecuteMsg};
+    use crate::state::{ProposalConfig, PauseScope, Role, PROPOSAL_CONFIG};
+    use crate::error::ContractError;
+    use crate::testing::{setup, exec, MANAGER};
+
+    const DELAY: u64 = 3_600;
+
+    fn proposal_config() -> ProposalConfig {
+        ProposalConfig { min_deposit: Uint128::new(1_000), endorsement_threshold: 10 }
+    }
+
+    #[test]
+    fn privileged_messages_go_through_the_queue() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::GrantRole {
+            address: "allocation_admin".to_string(),
+            role: Role::AllocationAdmin,
+        }).unwrap();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetAdminTimelock { delay_seconds: DELAY }).unwrap();
+
+        let err = exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetProposalConfig { config: proposal_config() })
+            .unwrap_err();
+        assert_eq!(err, ContractError::Timelocked);
+        let err = exec(&mut deps, mock_env(), "allocation_admin", ExecuteMsg::ProposeAllocationManager {
+            allocation_id: 1,
+            manager: "new_manager".to_string(),
+        }).unwrap_err();
+        assert_eq!(err, ContractError::Timelocked);
+        let err = exec(&mut deps, mock_env(), "allocation_admin", ExecuteMsg::RejectProposal {
+            proposal_id: 1,
+            slash: true,
+        }).unwrap_err();
+        assert_eq!(err, ContractError::Timelocked);
+
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::QueueAdminAction {
+            action: AdminAction::SetProposalConfig { config: proposal_config() },
+        }).unwrap();
+        let err = exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::ExecuteAdminAction { id: 1 }).unwrap_err();
+        assert_eq!(err.code(), 704);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DELAY);
+        exec(&mut deps, env, MANAGER, ExecuteMsg::ExecuteAdminAction { id: 1 }).unwrap();
+        assert_eq!(PROPOSAL_CONFIG.load(deps.as_ref().storage).unwrap(), proposal_config());
+    }
+
+    #[test]
+    fn pausing_is_exempt() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetAdminTimelock { delay_seconds: DELAY }).unwrap();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::SetPause {
+            scope: PauseScope::AllocationVoting,
+            paused: true,
+        }).unwrap();
+    }
+}
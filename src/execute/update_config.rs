@@ -1,21 +1,196 @@
 // src/execute/config.rs
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, StdResult};
-use crate::state::{CONFIG, Config};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Storage};
+use crate::msg::ConfigUpdate;
+use crate::state::{CONFIG, VOTING_CONFIG, VotingConfig, PendingManager, PENDING_MANAGER,
+    MANAGER_PROPOSAL_TTL_SECONDS};
+use crate::error::ContractError;
+use crate::execute::registry::clear_registry_cache;
+use crate::execute::vote_expiry::schedule_expiry_rescan;
 
+const MIN_REGISTRATION_VALIDITY_SECONDS: u64 = 86400; // 1 day
+const MAX_REGISTRATION_VALIDITY_SECONDS: u64 = 5 * 365 * 86400; // 5 years
+
+/// Record a changed field as `old_<name>`/`new_<name>` attributes
+fn changed(response: Response, name: &str, old: String, new: String) -> Response {
+    response
+        .add_attribute(format!("old_{}", name), old)
+        .add_attribute(format!("new_{}", name), new)
+}
+
+/// Update the given config fields; the contract manager changes through `ProposeManager`
 pub fn update_config(
     deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    config: Config,
-) -> StdResult<Response> {
-    let old_config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != old_config.contract_manager {
-        return Err(StdError::generic_err("Unauthorized"));
+    env: Env,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "update_config");
+
+    if let Some(address) = update.registration_address {
+        let address = deps.api.addr_validate(&address)?;
+        if address != config.registration_address {
+            response = changed(response, "registration_address", config.registration_address.to_string(), address.to_string());
+            config.registration_address = address;
+        }
+    }
+    if let Some(address) = update.registration_wallet {
+        let address = deps.api.addr_validate(&address)?;
+        if address != config.registration_wallet {
+            response = changed(response, "registration_wallet", config.registration_wallet.to_string(), address.to_string());
+            config.registration_wallet = address;
+        }
+    }
+    if let Some(seconds) = update.registration_validity_seconds {
+        if !(MIN_REGISTRATION_VALIDITY_SECONDS..=MAX_REGISTRATION_VALIDITY_SECONDS).contains(&seconds) {
+            return Err(ContractError::InvalidRegistrationValidity {
+                min: MIN_REGISTRATION_VALIDITY_SECONDS,
+                max: MAX_REGISTRATION_VALIDITY_SECONDS,
+            });
+        }
+        if seconds < config.registration_validity_seconds {
+            schedule_expiry_rescan(deps.storage, env.block.time, config.registration_validity_seconds)?;
+        }
+        if seconds != config.registration_validity_seconds {
+            response = changed(response, "registration_validity_seconds", config.registration_validity_seconds.to_string(), seconds.to_string());
+            config.registration_validity_seconds = seconds;
+        }
+    }
+    if let Some(address) = update.registry_contract {
+        let address = deps.api.addr_validate(&address)?;
+        if address != config.registry_contract {
+            response = changed(response, "registry_contract", config.registry_contract.to_string(), address.to_string());
+            config.registry_contract = address;
+            clear_registry_cache(deps.storage)?;
+        }
+    }
+    if let Some(hash) = update.registry_hash {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ContractError::InvalidRegistryHash);
+        }
+        if hash != config.registry_hash {
+            response = changed(response, "registry_hash", config.registry_hash.clone(), hash.clone());
+            config.registry_hash = hash;
+            clear_registry_cache(deps.storage)?;
+        }
     }
     
     CONFIG.save(deps.storage, &config)?;
     
+    Ok(response)
+}
+
+pub fn update_voting_config(
+    deps: DepsMut,
+    _env: Env,
+    voting_config: VotingConfig,
+) -> Result<Response, ContractError> {
+    // Existing votes keep their applied weight until they are refreshed or re-submitted
+    VOTING_CONFIG.save(deps.storage, &voting_config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_voting_config"))
+}
+
+/// Offer the contract manager role to `address`; it only changes hands once they accept
+/// Handing over the contract stays with the manager; an Admin role must not be able to claim it
+fn ensure_contract_manager(storage: &dyn Storage, info: &MessageInfo) -> Result<(), ContractError> {
+    if info.sender != CONFIG.load(storage)?.contract_manager {
+        return Err(ContractError::ManagerOnly);
+    }
+    Ok(())
+}
+
+pub fn propose_manager(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    ensure_contract_manager(deps.storage, &info)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let expires = env.block.time.plus_seconds(MANAGER_PROPOSAL_TTL_SECONDS);
+    PENDING_MANAGER.save(deps.storage, &PendingManager {
+        address: address.clone(),
+        expires,
+    })?;
+
     Ok(Response::new()
-        .add_attribute("action", "update_config"))
+        .add_attribute("action", "propose_manager")
+        .add_attribute("pending_manager", address.to_string())
+        .add_attribute("expires", expires.seconds().to_string()))
+}
+
+pub fn accept_manager(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_MANAGER.may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingManager)?;
+
+    if info.sender != pending.address {
+        return Err(ContractError::NotProposedAddress { role: "manager" });
+    }
+    if env.block.time > pending.expires {
+        return Err(ContractError::ManagerProposalExpired);
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous = config.contract_manager;
+    config.contract_manager = pending.address;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_MANAGER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_manager")
+        .add_attribute("previous_manager", previous.to_string())
+        .add_attribute("contract_manager", config.contract_manager.to_string()))
+}
+
+pub fn cancel_manager_proposal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ensure_contract_manager(deps.storage, &info)?;
+    if PENDING_MANAGER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingManager);
+    }
+
+    PENDING_MANAGER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_manager_proposal"))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use crate::msg::ExecuteMsg;
+    use crate::state::{Role, CONFIG};
+    use crate::error::ContractError;
+    use crate::testing::{setup, exec, MANAGER};
+
+    #[test]
+    fn admins_cannot_take_over_the_manager() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::GrantRole {
+            address: "admin".to_string(),
+            role: Role::Admin,
+        }).unwrap();
+
+        let propose = ExecuteMsg::ProposeManager { address: "admin".to_string() };
+        let err = exec(&mut deps, mock_env(), "admin", propose.clone()).unwrap_err();
+        assert_eq!(err, ContractError::ManagerOnly);
+
+        exec(&mut deps, mock_env(), MANAGER, propose).unwrap();
+        let err = exec(&mut deps, mock_env(), "admin", ExecuteMsg::CancelManagerProposal {}).unwrap_err();
+        assert_eq!(err, ContractError::ManagerOnly);
+
+        exec(&mut deps, mock_env(), "admin", ExecuteMsg::AcceptManager {}).unwrap();
+        assert_eq!(CONFIG.load(deps.as_ref().storage).unwrap().contract_manager.as_str(), "admin");
+    }
 }
@@ -0,0 +1,61 @@
+// src/execute/validation.rs
+use cosmwasm_std::{Deps, Addr, QueryRequest, WasmQuery, ContractInfoResponse};
+use crate::state::{AllocationConfig, AllocationRecipient, MAX_DESCRIPTION_LENGTH};
+use crate::error::AllocationConfigError;
+
+/// Reject addresses that aren't valid and in canonical (normalized) form
+fn validate_addr(deps: Deps, field: &'static str, addr: &Addr) -> Result<(), AllocationConfigError> {
+    match deps.api.addr_validate(addr.as_str()) {
+        Ok(validated) if &validated == addr => Ok(()),
+        _ => Err(AllocationConfigError::InvalidAddress { field, address: addr.to_string() }),
+    }
+}
+
+/// Check a payout destination. The runtime's contract info doesn't expose code hashes, so a
+/// `use_send` receiver is checked to be a deployed contract with a well-formed hash; a wrong
+/// hash for a real contract still only surfaces when the Send executes.
+pub fn validate_payee(
+    deps: Deps,
+    field: &'static str,
+    addr: &Addr,
+    code_hash: &Option<String>,
+    use_send: bool,
+) -> Result<(), AllocationConfigError> {
+    validate_addr(deps, field, addr)?;
+
+    if let Some(code_hash) = code_hash {
+        if code_hash.len() != 64 || !code_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AllocationConfigError::InvalidCodeHash { address: addr.to_string() });
+        }
+    }
+
+    if use_send {
+        if code_hash.is_none() {
+            return Err(AllocationConfigError::MissingCodeHash { address: addr.to_string() });
+        }
+        let query = QueryRequest::Wasm(WasmQuery::ContractInfo { contract_addr: addr.to_string() });
+        if deps.querier.query::<ContractInfoResponse>(&query).is_err() {
+            return Err(AllocationConfigError::NotAContract { address: addr.to_string() });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_allocation_config(deps: Deps, config: &AllocationConfig) -> Result<(), AllocationConfigError> {
+    if config.description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(AllocationConfigError::DescriptionTooLong { max: MAX_DESCRIPTION_LENGTH });
+    }
+    validate_payee(deps, "receive", &config.receive_addr, &config.receive_hash, config.use_send)?;
+    if let Some(manager_addr) = &config.manager_addr {
+        validate_addr(deps, "manager", manager_addr)?;
+    }
+    if let Some(claimer_addr) = &config.claimer_addr {
+        validate_addr(deps, "claimer", claimer_addr)?;
+    }
+    Ok(())
+}
+
+pub fn validate_recipient(deps: Deps, recipient: &AllocationRecipient) -> Result<(), AllocationConfigError> {
+    validate_payee(deps, "recipient", &recipient.addr, &recipient.code_hash, recipient.use_send)
+}
@@ -0,0 +1,174 @@
+// src/execute/vesting.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Storage, Timestamp};
+use crate::state::{ALLOCATION_OPTIONS, VestingSchedule, VestingTranche, MAX_VESTING_TRANCHES,
+    ALLOCATION_VESTING_DURATION, ALLOCATION_VESTING};
+use crate::execute::recipients::{allocation_recipients, transfer_to_recipients};
+use crate::execute::allocation_changes::apply_pending_payout;
+use crate::execute::registry::resolve_contracts;
+use crate::error::ContractError;
+
+/// Move the portion of each tranche that has vested by `now` into `withdrawable`,
+/// dropping tranches that are fully released
+pub fn release_vested(schedule: &mut VestingSchedule, now: Timestamp) {
+    for tranche in schedule.tranches.iter_mut() {
+        if now <= tranche.start {
+            continue;
+        }
+        let vested = if now >= tranche.end {
+            tranche.locked
+        } else {
+            tranche.locked.multiply_ratio(
+                now.seconds() - tranche.start.seconds(),
+                tranche.end.seconds() - tranche.start.seconds(),
+            )
+        };
+        tranche.locked -= vested;
+        schedule.withdrawable += vested;
+        tranche.start = now;
+    }
+    schedule.tranches.retain(|tranche| !tranche.locked.is_zero());
+}
+
+/// Lock a claimed amount in a new tranche vesting over `duration` from `now`. Once
+/// `MAX_VESTING_TRANCHES` are open it joins the newest tranche instead, ending at the
+/// amount-weighted average of the two ends; older tranches are never moved.
+pub fn lock_vesting(
+    storage: &mut dyn Storage,
+    allocation_id: u32,
+    amount: Uint128,
+    duration: u64,
+    now: Timestamp,
+) -> StdResult<()> {
+    let mut schedule = ALLOCATION_VESTING.get(storage, &allocation_id).unwrap_or_default();
+    release_vested(&mut schedule, now);
+
+    let open = schedule.tranches.len();
+    match schedule.tranches.last_mut() {
+        Some(newest) if open >= MAX_VESTING_TRANCHES => {
+            // Every open tranche starts at `now` after the release above
+            let combined = newest.locked + amount;
+            let remaining = newest.locked.multiply_ratio(newest.end.seconds() - now.seconds(), combined)
+                + amount.multiply_ratio(duration, combined);
+            newest.locked = combined;
+            newest.end = now.plus_seconds(remaining.u128() as u64);
+        }
+        _ => schedule.tranches.push(VestingTranche {
+            locked: amount,
+            start: now,
+            end: now.plus_seconds(duration),
+        }),
+    }
+    ALLOCATION_VESTING.insert(storage, &allocation_id, &schedule)
+}
+
+/// Set how long claimed rewards of an allocation vest; `None` or zero pays out on claim again.
+/// Amounts already locked keep vesting on their current schedule.
+pub fn set_allocation_vesting(
+    deps: DepsMut,
+    allocation_id: u32,
+    duration_seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    if !ALLOCATION_OPTIONS.contains(deps.storage, &allocation_id) {
+        return Err(ContractError::AllocationNotFound);
+    }
+
+    let duration = duration_seconds.unwrap_or(0);
+    if duration == 0 {
+        if ALLOCATION_VESTING_DURATION.contains(deps.storage, &allocation_id) {
+            ALLOCATION_VESTING_DURATION.remove(deps.storage, &allocation_id)?;
+        }
+    } else {
+        ALLOCATION_VESTING_DURATION.insert(deps.storage, &allocation_id, &duration)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_allocation_vesting")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("duration_seconds", duration.to_string()))
+}
+
+/// Pay out the vested part of an allocation's held rewards to its current recipients
+pub fn withdraw_vested(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    allocation_id: u32,
+) -> Result<Response, ContractError> {
+    let mut allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::AllocationNotFound)?;
+
+    // Same restriction as claiming
+    if let Some(claimer_addr) = &allocation.config.claimer_addr {
+        if &info.sender != claimer_addr {
+            return Err(ContractError::NotAllocationClaimer);
+        }
+    }
+
+    let mut schedule = ALLOCATION_VESTING.get(deps.storage, &allocation_id)
+        .ok_or(ContractError::NothingVesting)?;
+    release_vested(&mut schedule, env.block.time);
+
+    let amount = schedule.withdrawable;
+    if amount.is_zero() {
+        return Ok(Response::new()
+            .add_attribute("action", "withdraw_vested")
+            .add_attribute("allocation_id", allocation_id.to_string())
+            .add_attribute("amount", "0"));
+    }
+    schedule.withdrawable = Uint128::zero();
+
+    let contracts = resolve_contracts(deps.branch(), env.block.time, &["erth_token"])?;
+    let erth_token = &contracts[0];
+
+    // Recipients are resolved now, so edits made while vesting apply to the payout
+    if apply_pending_payout(deps.storage, &mut allocation, env.block.time)? {
+        ALLOCATION_OPTIONS.insert(deps.storage, &allocation_id, &allocation)?;
+    }
+    let recipients = allocation_recipients(deps.storage, &allocation);
+    let messages = transfer_to_recipients(erth_token, allocation_id, &recipients, amount)?;
+
+    if schedule.tranches.is_empty() {
+        ALLOCATION_VESTING.remove(deps.storage, &allocation_id)?;
+    } else {
+        ALLOCATION_VESTING.insert(deps.storage, &allocation_id, &schedule)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "withdraw_vested")
+        .add_attribute("allocation_id", allocation_id.to_string())
+        .add_attribute("amount", amount.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::{Timestamp, Uint128};
+    use crate::state::ALLOCATION_VESTING;
+    use super::{lock_vesting, release_vested};
+
+    #[test]
+    fn tranches_release_independently() {
+        let mut storage = MockStorage::new();
+        let start = Timestamp::from_seconds(1_000);
+        lock_vesting(&mut storage, 1, Uint128::new(1_000), 100, start).unwrap();
+        // Halfway through the first tranche a second, larger claim starts its own clock
+        lock_vesting(&mut storage, 1, Uint128::new(3_000), 100, start.plus_seconds(50)).unwrap();
+
+        let mut schedule = ALLOCATION_VESTING.get(&storage, &1).unwrap();
+        assert_eq!(schedule.withdrawable, Uint128::new(500));
+        assert_eq!(schedule.tranches.len(), 2);
+        assert_eq!(schedule.tranches[0].end, start.plus_seconds(100));
+        assert_eq!(schedule.tranches[1].end, start.plus_seconds(150));
+
+        // The first tranche finishes on time while the second is half released
+        release_vested(&mut schedule, start.plus_seconds(100));
+        assert_eq!(schedule.withdrawable, Uint128::new(1_000 + 1_500));
+        assert_eq!(schedule.tranches.len(), 1);
+        assert_eq!(schedule.locked(), Uint128::new(1_500));
+
+        release_vested(&mut schedule, start.plus_seconds(150));
+        assert_eq!(schedule.withdrawable, Uint128::new(4_000));
+        assert!(schedule.tranches.is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+// src/execute/vote_expiry.rs
+use cosmwasm_std::{DepsMut, Env, Response, StdResult, Uint128, Addr, Storage, Timestamp};
+use crate::state::{CONFIG, STATE, State, REGISTRATIONS, USER_ALLOCATIONS, USER_VOTES, UserAllocations, UserVote,
+    VOTE_EXPIRY_QUEUE, VOTE_EXPIRY_CURSOR, VoteExpiryRescan, VOTE_EXPIRY_RESCAN, VOTE_EXPIRY_BACKFILL, DELEGATIONS,
+    FLAT_VOTE_WEIGHT};
+use crate::execute::allocation::{update_reward_indexes, load_user_votes, remove_votes};
+use crate::execute::delegation::undelegate;
+use crate::execute::voter_reward::set_voter_weight;
+use crate::error::ContractError;
+
+const SECONDS_IN_A_DAY: u64 = 86400;
+/// Expiries handled as a side effect of other calls, keeping their gas bounded
+pub const LAZY_EXPIRY_LIMIT: u32 = 10;
+const DEFAULT_KEEPER_LIMIT: u32 = 50;
+const MAX_KEEPER_LIMIT: u32 = 200;
+
+/// Queue a voter to be checked once their registration lapses
+pub fn enqueue_vote_expiry(storage: &mut dyn Storage, address: &Addr, expires: Timestamp) -> StdResult<()> {
+    let day = expires.seconds() / SECONDS_IN_A_DAY;
+    let mut bucket = VOTE_EXPIRY_QUEUE.get(storage, &day).unwrap_or_default();
+    if !bucket.contains(address) {
+        bucket.push(address.clone());
+        VOTE_EXPIRY_QUEUE.insert(storage, &day, &bucket)?;
+    }
+    match VOTE_EXPIRY_CURSOR.may_load(storage)? {
+        Some(cursor) if cursor <= day => {}
+        _ => VOTE_EXPIRY_CURSOR.save(storage, &day)?,
+    }
+    Ok(())
+}
+
+/// Expiry currently tracked for an address: its delegation if it delegates, otherwise its vote
+fn tracked_expiry(storage: &dyn Storage, address: &Addr) -> Option<Timestamp> {
+    match DELEGATIONS.get(storage, address) {
+        Some(delegation) => Some(delegation.expires),
+        None => USER_VOTES.get(storage, address).map(|vote| vote.expires),
+    }
+}
+
+/// Move an address's tracked expiry earlier, to when its registration now lapses
+fn set_tracked_expiry(storage: &mut dyn Storage, address: &Addr, expires: Timestamp) -> StdResult<()> {
+    if let Some(mut delegation) = DELEGATIONS.get(storage, address) {
+        delegation.expires = expires;
+        return DELEGATIONS.insert(storage, address, &delegation);
+    }
+    if let Some(mut vote) = USER_VOTES.get(storage, address) {
+        vote.expires = expires;
+        USER_VOTES.insert(storage, address, &vote)?;
+    }
+    Ok(())
+}
+
+/// Re-check every expiry day from today up to the latest expiry `old_validity` allowed, so
+/// queued votes lapse under a shortened validity rather than on their old day
+pub fn schedule_expiry_rescan(storage: &mut dyn Storage, now: Timestamp, old_validity: u64) -> StdResult<()> {
+    let today = now.seconds() / SECONDS_IN_A_DAY;
+    let last_day = now.plus_seconds(old_validity).seconds() / SECONDS_IN_A_DAY;
+    let rescan = match VOTE_EXPIRY_RESCAN.may_load(storage)? {
+        // An unfinished rescan restarts from today, covering both ranges
+        Some(rescan) => VoteExpiryRescan { next_day: today, next_index: 0, last_day: rescan.last_day.max(last_day) },
+        None => VoteExpiryRescan { next_day: today, next_index: 0, last_day },
+    };
+    VOTE_EXPIRY_RESCAN.save(storage, &rescan)
+}
+
+/// Move queued voters whose registration now lapses before their queued day to the earlier
+/// day, doing at most `limit` units of work. Returns the work done.
+fn rescan_vote_expiries(storage: &mut dyn Storage, limit: u32) -> Result<u32, ContractError> {
+    let mut rescan = match VOTE_EXPIRY_RESCAN.may_load(storage)? {
+        Some(rescan) => rescan,
+        None => return Ok(0),
+    };
+    let config = CONFIG.load(storage)?;
+    let mut work = 0u32;
+
+    while rescan.next_day <= rescan.last_day && work < limit {
+        let day = rescan.next_day;
+        let mut bucket = VOTE_EXPIRY_QUEUE.get(storage, &day).unwrap_or_default();
+        let mut index = rescan.next_index as usize;
+        while index < bucket.len() && work < limit {
+            work += 1;
+            let address = bucket[index].clone();
+            let current_expiry = REGISTRATIONS.get_by_address(storage, &address)?
+                .map(|registration| registration.registration_timestamp.plus_seconds(config.registration_validity_seconds));
+            match (tracked_expiry(storage, &address), current_expiry) {
+                (Some(tracked), Some(current)) if current < tracked => {
+                    bucket.remove(index);
+                    set_tracked_expiry(storage, &address, current)?;
+                    enqueue_vote_expiry(storage, &address, current)?;
+                }
+                (None, _) => {
+                    bucket.remove(index);
+                }
+                _ => index += 1,
+            }
+        }
+
+        if bucket.is_empty() {
+            if VOTE_EXPIRY_QUEUE.contains(storage, &day) {
+                VOTE_EXPIRY_QUEUE.remove(storage, &day)?;
+            }
+        } else {
+            VOTE_EXPIRY_QUEUE.insert(storage, &day, &bucket)?;
+        }
+        if index < bucket.len() {
+            rescan.next_index = index as u32;
+            break;
+        }
+        rescan.next_day += 1;
+        rescan.next_index = 0;
+        work += 1;
+    }
+
+    if rescan.next_day > rescan.last_day {
+        VOTE_EXPIRY_RESCAN.remove(storage);
+    } else {
+        VOTE_EXPIRY_RESCAN.save(storage, &rescan)?;
+    }
+    Ok(work)
+}
+
+/// Tie votes cast before the expiry queue existed to their voter's registration and queue
+/// them, doing at most `limit` units of work. Returns the work done.
+fn backfill_vote_expiries(storage: &mut dyn Storage, epoch: u32, now: Timestamp, limit: u32) -> Result<u32, ContractError> {
+    let mut position = match VOTE_EXPIRY_BACKFILL.may_load(storage)? {
+        Some(position) => position,
+        None => return Ok(0),
+    };
+    if limit == 0 {
+        return Ok(0);
+    }
+    let config = CONFIG.load(storage)?;
+    // Entries are never removed, so positions stay put while the backfill is spread over calls
+    let entries = USER_ALLOCATIONS.iter(storage)?
+        .skip(position as usize)
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = entries.len() < limit as usize;
+    let work = entries.len() as u32;
+
+    for (address, user_allocations) in entries {
+        position += 1;
+        // Anything voted or delegated since the upgrade is queued already
+        let tracked = DELEGATIONS.contains(storage, &address)
+            || USER_VOTES.get(storage, &address).is_some_and(|vote| vote.epoch == epoch);
+        if tracked || user_allocations.epoch != epoch || user_allocations.allocations.is_empty() {
+            continue;
+        }
+        let expires = REGISTRATIONS.get_by_address(storage, &address)?
+            .map(|registration| registration.registration_timestamp.plus_seconds(config.registration_validity_seconds))
+            .unwrap_or(now);
+        USER_VOTES.insert(storage, &address, &UserVote {
+            epoch,
+            weight: Uint128::from(FLAT_VOTE_WEIGHT),
+            expires,
+        })?;
+        // Long-lapsed votes go in today's bucket rather than rewinding the cursor day by day
+        enqueue_vote_expiry(storage, &address, expires.max(now))?;
+    }
+
+    if done {
+        VOTE_EXPIRY_BACKFILL.remove(storage);
+    } else {
+        VOTE_EXPIRY_BACKFILL.save(storage, &position)?;
+    }
+    Ok(work)
+}
+
+/// Remove a lapsed voter's weight (their vote, or their delegation and everything flowing
+/// through it), settling allocations up to the moment it lapsed. A voter who holds a valid
+/// registration again (re-registered, or validity was extended) is re-queued at the new expiry.
+fn expire_voter(storage: &mut dyn Storage, state: &mut State, address: &Addr, expires: Timestamp, now: Timestamp) -> Result<bool, ContractError> {
+    let delegation = DELEGATIONS.get(storage, address);
+    let (allocations, weight) = load_user_votes(storage, address, state.epoch);
+    if delegation.is_none() && allocations.is_empty() {
+        return Ok(false);
+    }
+
+    let config = CONFIG.load(storage)?;
+    if let Some(registration) = REGISTRATIONS.get_by_address(storage, address)? {
+        let current_expiry = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
+        if current_expiry > now {
+            match delegation {
+                Some(mut delegation) => {
+                    delegation.expires = current_expiry;
+                    DELEGATIONS.insert(storage, address, &delegation)?;
+                }
+                None => USER_VOTES.insert(storage, address, &UserVote {
+                    epoch: state.epoch,
+                    weight,
+                    expires: current_expiry,
+                })?,
+            }
+            enqueue_vote_expiry(storage, address, current_expiry)?;
+            return Ok(false);
+        }
+    }
+
+    // Rewards earned before the expiry still count; the index can't be moved backwards
+    if expires > state.last_upkeep {
+        update_reward_indexes(storage, state, expires)?;
+    }
+    if delegation.is_some() {
+        undelegate(storage, state, address)?;
+    } else {
+        remove_votes(storage, state, &allocations, weight)?;
+        set_voter_weight(storage, state.epoch, address, Uint128::zero())?;
+        USER_ALLOCATIONS.insert(storage, address, &UserAllocations {
+            epoch: state.epoch,
+            allocations: vec![],
+        })?;
+    }
+    Ok(true)
+}
+
+/// Drain due entries from the expiry queue in expiry order, doing at most `limit` units of work.
+/// Must run before the reward index is brought up to `now`. Returns how many votes were removed.
+pub fn process_vote_expiries(storage: &mut dyn Storage, state: &mut State, now: Timestamp, limit: u32) -> Result<u32, ContractError> {
+    // Votes moved earlier by a validity rescan or queued by the backfill may rewind the
+    // cursor, so both run first
+    let mut work = rescan_vote_expiries(storage, limit)?;
+    work += backfill_vote_expiries(storage, state.epoch, now, limit - work)?;
+    let mut cursor = match VOTE_EXPIRY_CURSOR.may_load(storage)? {
+        Some(cursor) => cursor,
+        None => return Ok(0),
+    };
+    let today = now.seconds() / SECONDS_IN_A_DAY;
+    let mut removed = 0u32;
+
+    while cursor <= today && work < limit {
+        // Take the bucket out so votes re-queued for later today land in a fresh one
+        let bucket = VOTE_EXPIRY_QUEUE.get(storage, &cursor).unwrap_or_default();
+        if VOTE_EXPIRY_QUEUE.contains(storage, &cursor) {
+            VOTE_EXPIRY_QUEUE.remove(storage, &cursor)?;
+        }
+
+        // Split the bucket into votes that are due and votes lapsing later today; entries
+        // whose vote was re-cast with another expiry are stale and are dropped
+        let mut due = vec![];
+        let mut waiting = vec![];
+        for address in bucket {
+            match tracked_expiry(storage, &address) {
+                Some(expires) if expires <= now => due.push((expires, address)),
+                Some(expires) if expires.seconds() / SECONDS_IN_A_DAY == cursor => waiting.push(address),
+                _ => {}
+            }
+        }
+        due.sort();
+
+        let budget = (limit - work) as usize;
+        if due.len() > budget {
+            waiting.extend(due.split_off(budget).into_iter().map(|(_, address)| address));
+        }
+        for (expires, address) in due {
+            if expire_voter(storage, state, &address, expires, now)? {
+                removed += 1;
+            }
+            work += 1;
+        }
+
+        if waiting.is_empty() && !VOTE_EXPIRY_QUEUE.contains(storage, &cursor) {
+            cursor += 1;
+            work += 1;
+        } else {
+            let mut remaining = VOTE_EXPIRY_QUEUE.get(storage, &cursor).unwrap_or_default();
+            for address in waiting {
+                if !remaining.contains(&address) {
+                    remaining.push(address);
+                }
+            }
+            VOTE_EXPIRY_QUEUE.insert(storage, &cursor, &remaining)?;
+            break;
+        }
+    }
+
+    VOTE_EXPIRY_CURSOR.save(storage, &cursor)?;
+    Ok(removed)
+}
+
+/// Keeper entry point for draining the expiry queue when the contract sees little traffic
+pub fn process_vote_expiries_msg(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_KEEPER_LIMIT).min(MAX_KEEPER_LIMIT);
+
+    let mut state = STATE.load(deps.storage)?;
+    let removed = process_vote_expiries(deps.storage, &mut state, env.block.time, limit)?;
+    update_reward_indexes(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "process_vote_expiries")
+        .add_attribute("removed", removed.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{Addr, Uint128};
+    use crate::msg::ExecuteMsg;
+    use crate::state::{STATE, ALLOCATION_OPTIONS, USER_VOTES, VOTE_EXPIRY_QUEUE, VOTE_EXPIRY_CURSOR,
+        VOTE_EXPIRY_BACKFILL};
+    use crate::testing::{setup, exec, register, add_allocation, vote, TestDeps, MANAGER};
+
+    const VALIDITY_SECONDS: u64 = 60 * 60 * 24 * 30;
+
+    fn process(deps: &mut TestDeps, seconds_later: u64) {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds_later);
+        exec(deps, env, MANAGER, ExecuteMsg::ProcessVoteExpiries { limit: None }).unwrap();
+    }
+
+    #[test]
+    fn votes_from_before_the_queue_expire_after_the_backfill() {
+        let mut deps = setup();
+        let allocation_id = add_allocation(&mut deps, "project");
+        register(&mut deps, mock_env(), "voter");
+        vote(&mut deps, mock_env(), "voter", &[(allocation_id, 100)]).unwrap();
+
+        // Storage as an upgraded deployment has it: the vote is tallied but neither tracked nor queued
+        let storage = deps.as_mut().storage;
+        let voter = Addr::unchecked("voter");
+        USER_VOTES.remove(storage, &voter).unwrap();
+        let day = mock_env().block.time.plus_seconds(VALIDITY_SECONDS).seconds() / 86_400;
+        VOTE_EXPIRY_QUEUE.remove(storage, &day).unwrap();
+        VOTE_EXPIRY_CURSOR.remove(storage);
+        VOTE_EXPIRY_BACKFILL.save(storage, &0).unwrap();
+
+        let tally = |deps: &TestDeps| {
+            ALLOCATION_OPTIONS.get(deps.as_ref().storage, &allocation_id).unwrap().state.amount_allocated
+        };
+        let tallied = tally(&deps);
+        assert!(!tallied.is_zero());
+
+        // Before the registration lapses the backfill only queues the vote
+        process(&mut deps, 60);
+        assert!(VOTE_EXPIRY_BACKFILL.may_load(deps.as_ref().storage).unwrap().is_none());
+        assert_eq!(VOTE_EXPIRY_QUEUE.get(deps.as_ref().storage, &day), Some(vec![voter.clone()]));
+        assert_eq!(tally(&deps), tallied);
+
+        process(&mut deps, VALIDITY_SECONDS + 1);
+        assert_eq!(tally(&deps), Uint128::zero());
+        assert_eq!(STATE.load(deps.as_ref().storage).unwrap().total_allocations, Uint128::zero());
+    }
+}
@@ -0,0 +1,191 @@
+// src/execute/voter_reward.rs
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Addr, Storage, Timestamp};
+use crate::state::{STATE, VoterRewardConfig, VoterRewardPool, VoterReward, MAX_VOTER_REWARD_BPS,
+    VOTER_REWARD_CONFIG, VOTER_REWARD_POOL, VOTER_REWARDS, EPOCH_VOTER_INDEX};
+use crate::execute::allocation::{sync_allocations, INDEX_PRECISION, REWARD_RATE};
+use crate::execute::recipients::mint_msg;
+use crate::execute::registry::resolve_contracts;
+use crate::execute::reward_stream::{settle_voter_token_rewards, close_voter_token_epoch};
+use crate::error::ContractError;
+
+pub fn voter_reward_bps(storage: &dyn Storage) -> u16 {
+    VOTER_REWARD_CONFIG.may_load(storage).ok().flatten().unwrap_or_default().voter_reward_bps
+}
+
+/// Add the voter slice of the emission between `last_upkeep` and `current_time` to the voter index
+pub fn update_voter_index(
+    storage: &mut dyn Storage,
+    last_upkeep: Timestamp,
+    current_time: Timestamp,
+) -> StdResult<()> {
+    let bps = voter_reward_bps(storage);
+    let time_elapsed = current_time.seconds().saturating_sub(last_upkeep.seconds());
+    if bps == 0 || time_elapsed == 0 {
+        return Ok(());
+    }
+    let mut pool = VOTER_REWARD_POOL.may_load(storage)?.unwrap_or_default();
+    if pool.total_weight.is_zero() {
+        return Ok(());
+    }
+    let new_rewards = (Uint128::from(time_elapsed) * Uint128::from(REWARD_RATE))
+        .multiply_ratio(bps as u128, 10_000u128);
+    pool.reward_index += new_rewards.multiply_ratio(INDEX_PRECISION, pool.total_weight);
+    VOTER_REWARD_POOL.save(storage, &pool)
+}
+
+/// Credit a voter with what they earned since their last settlement. Weight from a past epoch
+/// only earns up to that epoch's reset and is then dropped.
+fn settle_voter(storage: &dyn Storage, pool: &VoterRewardPool, epoch: u32, address: &Addr) -> VoterReward {
+    let mut voter = VOTER_REWARDS.get(storage, address).unwrap_or_default();
+    let index = if voter.epoch == epoch {
+        pool.reward_index
+    } else {
+        EPOCH_VOTER_INDEX.get(storage, &voter.epoch).unwrap_or(voter.last_reward_index)
+    };
+    voter.accumulated_rewards += voter.weight.multiply_ratio(index - voter.last_reward_index, INDEX_PRECISION);
+    voter.last_reward_index = index;
+    if voter.epoch != epoch {
+        voter.epoch = epoch;
+        voter.weight = Uint128::zero();
+        voter.last_reward_index = pool.reward_index;
+    }
+    voter
+}
+
+/// Record the weight a user's vote carries in the current epoch, settling first.
+/// Call whenever a vote is cast, re-weighted or withdrawn, after the indexes are synced.
+pub fn set_voter_weight(storage: &mut dyn Storage, epoch: u32, address: &Addr, weight: Uint128) -> Result<(), ContractError> {
+    let mut pool = VOTER_REWARD_POOL.may_load(storage)?.unwrap_or_default();
+    let mut voter = settle_voter(storage, &pool, epoch, address);
+    // Nothing to record for users who never earned voter rewards
+    if voter.weight.is_zero() && weight.is_zero() && voter.accumulated_rewards.is_zero() {
+        return Ok(());
+    }
+    settle_voter_token_rewards(storage, address, epoch)?;
+
+    pool.total_weight = pool.total_weight.checked_sub(voter.weight)
+        .map_err(|_| ContractError::Underflow("voter weight"))? + weight;
+    voter.weight = weight;
+
+    VOTER_REWARD_POOL.save(storage, &pool)?;
+    VOTER_REWARDS.insert(storage, address, &voter)?;
+    Ok(())
+}
+
+/// Freeze the voter index for the closing epoch and start the next one with no weight
+pub fn close_voter_epoch(storage: &mut dyn Storage, epoch: u32) -> StdResult<()> {
+    let mut pool = VOTER_REWARD_POOL.may_load(storage)?.unwrap_or_default();
+    EPOCH_VOTER_INDEX.insert(storage, &epoch, &pool.reward_index)?;
+    close_voter_token_epoch(storage, epoch)?;
+    pool.total_weight = Uint128::zero();
+    VOTER_REWARD_POOL.save(storage, &pool)
+}
+
+/// Rewards a voter could claim right now, given an up-to-date pool
+pub fn pending_voter_rewards(storage: &dyn Storage, pool: &VoterRewardPool, epoch: u32, address: &Addr) -> VoterReward {
+    settle_voter(storage, pool, epoch, address)
+}
+
+pub fn claim_voter_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    let pool = VOTER_REWARD_POOL.may_load(deps.storage)?.unwrap_or_default();
+    let mut voter = settle_voter(deps.storage, &pool, state.epoch, &info.sender);
+    let amount = voter.accumulated_rewards;
+    if amount.is_zero() {
+        return Ok(Response::new()
+            .add_attribute("action", "claim_voter_rewards")
+            .add_attribute("amount", "0"));
+    }
+    voter.accumulated_rewards = Uint128::zero();
+    VOTER_REWARDS.insert(deps.storage, &info.sender, &voter)?;
+
+    let contracts = resolve_contracts(deps.branch(), env.block.time, &["erth_token"])?;
+    let erth_token = &contracts[0];
+
+    Ok(Response::new()
+        .add_message(mint_msg(erth_token, info.sender.to_string(), amount)?)
+        .add_attribute("action", "claim_voter_rewards")
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn set_voter_reward_config(
+    deps: DepsMut,
+    env: Env,
+    voter_reward_config: VoterRewardConfig,
+) -> Result<Response, ContractError> {
+    if voter_reward_config.voter_reward_bps > MAX_VOTER_REWARD_BPS {
+        return Err(ContractError::VoterRewardShareTooHigh { max: MAX_VOTER_REWARD_BPS });
+    }
+
+    // Emission so far is split at the old share
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    STATE.save(deps.storage, &state)?;
+
+    VOTER_REWARD_CONFIG.save(deps.storage, &voter_reward_config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_voter_reward_config")
+        .add_attribute("voter_reward_bps", voter_reward_config.voter_reward_bps.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{from_binary, Env, Uint128};
+    use crate::msg::{ExecuteMsg, QueryMsg, VoterRewardResponse};
+    use crate::state::{VotingConfig, VotingWeightMode, VoterRewardConfig};
+    use crate::testing::{setup, exec, register, add_allocation, vote, TestDeps, MANAGER};
+    use crate::query;
+
+    const HALF_VALIDITY_SECONDS: u64 = 60 * 60 * 24 * 15;
+
+    fn at(seconds_later: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds_later);
+        env
+    }
+
+    fn pending(deps: &TestDeps, env: Env, address: &str) -> Uint128 {
+        let rewards: VoterRewardResponse = from_binary(&query(deps.as_ref(), env, QueryMsg::QueryVoterRewards {
+            address: address.to_string(),
+        }).unwrap()).unwrap();
+        rewards.pending
+    }
+
+    #[test]
+    fn voter_share_splits_by_weight_until_the_epoch_closes() {
+        let mut deps = setup();
+        exec(&mut deps, mock_env(), MANAGER, ExecuteMsg::UpdateVotingConfig {
+            config: VotingConfig { weight_mode: VotingWeightMode::Seniority },
+        }).unwrap();
+        let allocation_id = add_allocation(&mut deps, "project");
+
+        // Halfway through its validity alice's registration weighs 1.5x bob's fresh one
+        register(&mut deps, mock_env(), "alice");
+        let start = HALF_VALIDITY_SECONDS;
+        register(&mut deps, at(start), "bob");
+        exec(&mut deps, at(start), MANAGER, ExecuteMsg::SetVoterRewardConfig {
+            config: VoterRewardConfig { voter_reward_bps: 2_000 },
+        }).unwrap();
+        vote(&mut deps, at(start), "alice", &[(allocation_id, 100)]).unwrap();
+        vote(&mut deps, at(start), "bob", &[(allocation_id, 100)]).unwrap();
+
+        // 20% of 1,000 seconds of emission, shared 3:2
+        let reset = start + 1_000;
+        assert_eq!(pending(&deps, at(reset), "alice"), Uint128::new(120_000_000));
+        assert_eq!(pending(&deps, at(reset), "bob"), Uint128::new(80_000_000));
+
+        // Weight from the closed epoch stops earning at the reset
+        exec(&mut deps, at(reset), MANAGER, ExecuteMsg::ResetAllocations {}).unwrap();
+        assert_eq!(pending(&deps, at(reset + 1_000), "alice"), Uint128::new(120_000_000));
+        assert_eq!(pending(&deps, at(reset + 1_000), "bob"), Uint128::new(80_000_000));
+    }
+}
@@ -0,0 +1,74 @@
+// src/execute/voting.rs
+use cosmwasm_std::{Deps, DepsMut, Env, Response, StdResult, Uint128};
+use crate::state::{CONFIG, STATE, REGISTRATIONS, VOTING_CONFIG, Config, Registration, VotingWeightMode,
+    FLAT_VOTE_WEIGHT, query_anml_balance};
+use crate::execute::allocation::{sync_allocations, reweight_votes};
+use crate::error::ContractError;
+use crate::execute::registry::lookup_contracts;
+
+const ANML_UNIT: u128 = 1_000_000; // 1 ANML (6 decimal places)
+
+/// Weight a registered user's allocations carry under the configured voting mode
+pub fn voting_weight(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    registration: &Registration,
+) -> StdResult<Uint128> {
+    let voting_config = VOTING_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let flat = Uint128::from(FLAT_VOTE_WEIGHT);
+
+    let weight = match voting_config.weight_mode {
+        VotingWeightMode::Flat => flat,
+        VotingWeightMode::Seniority => {
+            // Linear bonus over the validity period, so a registration about to expire counts double
+            let validity = config.registration_validity_seconds;
+            let age = env.block.time.seconds()
+                .saturating_sub(registration.registration_timestamp.seconds())
+                .min(validity);
+            if validity == 0 {
+                flat
+            } else {
+                flat + flat.multiply_ratio(age, validity)
+            }
+        }
+        VotingWeightMode::AnmlBalance => {
+            let (contracts, _) = lookup_contracts(deps, env.block.time, &["anml_token"])?;
+            let balance = query_anml_balance(&deps, &contracts[0], &registration.address)?;
+            balance.multiply_ratio(FLAT_VOTE_WEIGHT, ANML_UNIT)
+        }
+    };
+
+    Ok(weight)
+}
+
+/// Re-apply a user's current allocations with their up-to-date voting weight.
+/// Anyone may call this, so weights can follow seniority or balance changes without a new vote.
+pub fn refresh_allocation_weight(
+    deps: DepsMut,
+    env: Env,
+    address: String,
+) -> Result<Response, ContractError> {
+    let addr = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let registration = REGISTRATIONS.get_by_address(deps.storage, &addr)?
+        .ok_or(ContractError::NotRegistered)?;
+    let registration_age = env.block.time.seconds() - registration.registration_timestamp.seconds();
+    if registration_age > config.registration_validity_seconds {
+        return Err(ContractError::RegistrationExpired);
+    }
+
+    let expires = registration.registration_timestamp.plus_seconds(config.registration_validity_seconds);
+    let weight = voting_weight(deps.as_ref(), &env, &config, &registration)?;
+
+    let mut state = STATE.load(deps.storage)?;
+    sync_allocations(deps.storage, &mut state, env.block.time)?;
+    reweight_votes(deps.storage, &mut state, &addr, weight, expires)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "refresh_allocation_weight")
+        .add_attribute("address", addr.to_string())
+        .add_attribute("weight", weight.to_string()))
+}
@@ -1,14 +1,15 @@
 // src/execute/instantiate.rs
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
 use crate::msg::InstantiateMsg;
-use crate::state::{Config, STATE, CONFIG, State};
+use crate::state::{Config, STATE, CONFIG, State, ContractVersion, CONTRACT_NAME, CONTRACT_VERSION, STORED_VERSION};
+use crate::error::ContractError;
 
 pub fn execute_instantiate(
     deps: DepsMut,
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let registration_address_addr = deps.api.addr_validate(&msg.registration_address)?;
     let contract_manager_addr = deps.api.addr_validate(&msg.contract_manager)?;
     let registration_wallet_addr = deps.api.addr_validate(&msg.registration_wallet)?;
@@ -36,6 +37,10 @@ pub fn execute_instantiate(
 
     STATE.save(deps.storage, &state)?;
     CONFIG.save(deps.storage, &config)?;
+    STORED_VERSION.save(deps.storage, &ContractVersion {
+        contract: CONTRACT_NAME.to_string(),
+        version: CONTRACT_VERSION.to_string(),
+    })?;
 
     Ok(Response::default())
 }
@@ -1,9 +1,12 @@
 // src/lib.rs
+// Test builds declare the state keymaps `const` (see `keymap!` in state)
+#![cfg_attr(test, allow(clippy::declare_interior_mutable_const, clippy::borrow_interior_mutable_const))]
 use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use crate::execute::execute_dispatch;
 use crate::query::query_dispatch;
 use crate::migrate::perform_migration;
 use crate::instantiate::execute_instantiate;
+use crate::error::ContractError;
 
 pub mod msg;
 pub mod state;
@@ -11,6 +14,9 @@ pub mod execute;
 pub mod query;
 pub mod migrate;
 pub mod instantiate;
+pub mod error;
+#[cfg(test)]
+mod testing;
 
 #[entry_point]
 pub fn instantiate(
@@ -18,7 +24,7 @@ pub fn instantiate(
     env: Env,
     info: MessageInfo,
     msg: msg::InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     execute_instantiate(deps, env, info, msg)
 }
 
@@ -28,7 +34,7 @@ pub fn execute(
     env: Env, 
     info: MessageInfo, 
     msg: msg::ExecuteMsg
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     execute_dispatch(deps, env, info, msg)
 }
 
@@ -37,7 +43,7 @@ pub fn migrate(
     deps: DepsMut, 
     env: Env, 
     msg: msg::MigrateMsg
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     perform_migration(deps, env, msg)
 }
 
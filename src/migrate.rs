@@ -1,15 +1,19 @@
 // src/migrate.rs
-use cosmwasm_std::{DepsMut, Env, Response, StdResult, to_binary, Uint128, Addr, Timestamp,
+use cosmwasm_std::{DepsMut, Env, Response, StdError, StdResult, Storage, to_binary, to_vec, Uint128, Addr, Timestamp,
     CosmosMsg, WasmMsg};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use secret_toolkit::snip20;
 use secret_toolkit_storage::Item;
-use crate::msg::MigrateMsg;
+use crate::msg::{MigrateMsg, MigrationPreviewResponse, AllocationMigrationPreview};
 use crate::state::{
     Config, CONFIG, State, STATE, ALLOCATION_OPTIONS, ALLOCATION_IDS,
     Allocation, AllocationState, AllocationConfig,
+    ContractVersion, CONTRACT_NAME, CONTRACT_VERSION, STORED_VERSION, VOTE_EXPIRY_BACKFILL,
 };
+use crate::execute::invariants::allocation_invariants;
+use crate::execute::allocation::{update_reward_indexes, settle_allocation};
+use crate::error::ContractError;
 
 // Old types matching what's currently in storage (bincode format)
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -59,70 +63,183 @@ pub struct OldAllocation {
     pub config: OldAllocationConfig,
 }
 
-pub fn perform_migration(
-    deps: DepsMut,
-    env: Env,
-    msg: MigrateMsg,
-) -> StdResult<Response> {
-    match msg {
-        MigrateMsg::Migrate {
-            registry_contract,
-            registry_hash,
-        } => migrate_state(deps, env, registry_contract, registry_hash),
-        MigrateMsg::Upgrade {} => {
-            Ok(Response::new().add_attribute("action", "upgrade"))
+/// A storage upgrade that brings a contract up to `version`
+struct MigrationStep {
+    version: &'static str,
+    apply: fn(DepsMut, &Env, &MigrateMsg) -> Result<StepOutcome, ContractError>,
+}
+
+/// What a step did, including whether it consumed the registry details of `Migrate`
+struct StepOutcome {
+    response: Response,
+    used_registry: bool,
+}
+
+/// Version reached by the step that rewrites the original release's storage
+const LEGACY_STEP_VERSION: &str = "0.0.2";
+
+/// Version reached by the step that moves tallies into basis-point units
+const RESCALE_STEP_VERSION: &str = "0.1.0";
+
+/// Storage upgrades in version order; steps for the same version run in list order. Releases
+/// that only add new storage keys (which read as defaults when missing) don't need a step; the
+/// stored version is still bumped.
+const MIGRATION_STEPS: &[MigrationStep] = &[
+    MigrationStep { version: LEGACY_STEP_VERSION, apply: migrate_state },
+    MigrationStep { version: RESCALE_STEP_VERSION, apply: rescale_tallies },
+    MigrationStep { version: RESCALE_STEP_VERSION, apply: schedule_vote_expiry_backfill },
+];
+
+// 0.0.2 tallied a flat-weight vote as its whole percent; `allocation_amount` now counts
+// `weight * basis_points / 100`, i.e. 100x that for the flat weight of 100
+const TALLY_RESCALE: u128 = 100;
+
+type Version = (u64, u64, u64);
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    let invalid = || ContractError::InvalidVersion { version: version.to_string() };
+    let parts = version.split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    match parts[..] {
+        [major, minor, patch] => Ok((major, minor, patch)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Steps that move storage from version `from` up to `to`, in order
+fn pending_steps(from: Version, to: Version) -> Result<Vec<&'static MigrationStep>, ContractError> {
+    let mut steps = vec![];
+    for step in MIGRATION_STEPS {
+        let version = parse_version(step.version)?;
+        if version > from && version <= to {
+            steps.push(step);
         }
     }
+    Ok(steps)
 }
 
-fn migrate_state(
-    deps: DepsMut,
-    env: Env,
-    registry_contract: String,
-    registry_hash: String,
-) -> StdResult<Response> {
+/// Version recorded in storage. Deployments from before version tracking are told apart by
+/// their state layout: the original release stored `OldState`, 0.0.2 the current `State`.
+pub fn stored_version(storage: &dyn Storage) -> StdResult<ContractVersion> {
+    if let Some(version) = STORED_VERSION.may_load(storage)? {
+        return Ok(version);
+    }
+    let version = if STATE.load(storage).is_ok() { "0.0.2" } else { "0.0.1" };
+    Ok(ContractVersion {
+        contract: CONTRACT_NAME.to_string(),
+        version: version.to_string(),
+    })
+}
 
-    // Load old config and convert to new format
-    let old_config_storage: Item<OldConfig> = Item::new(b"config");
-    let old_config = old_config_storage.load(deps.storage)?;
+pub fn perform_migration(
+    mut deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let stored = stored_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract {
+            stored: stored.contract,
+            current: CONTRACT_NAME.to_string(),
+        });
+    }
 
-    let registry_addr = deps.api.addr_validate(&registry_contract)?;
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(ContractError::MigrationDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
 
-    let new_config = Config {
-        registration_address: old_config.registration_address,
-        registration_wallet: old_config.registration_wallet,
-        contract_manager: old_config.contract_manager,
-        registration_validity_seconds: old_config.registration_validity_seconds,
-        registry_contract: registry_addr,
-        registry_hash: registry_hash.clone(),
+    let dry_run = match msg {
+        MigrateMsg::Migrate { dry_run, .. } | MigrateMsg::Upgrade { dry_run } => dry_run,
     };
-    CONFIG.save(deps.storage, &new_config)?;
+    let steps = pending_steps(from, to)?;
+    let before = if dry_run {
+        let legacy = steps.iter().any(|step| step.version == LEGACY_STEP_VERSION);
+        Some(snapshot_allocations(deps.storage, legacy, env.block.time)?)
+    } else {
+        None
+    };
+
+    let mut response = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version.clone())
+        .add_attribute("to_version", CONTRACT_VERSION);
+
+    let mut registry_used = false;
+    for step in &steps {
+        let outcome = (step.apply)(deps.branch(), &env, &msg)?;
+        registry_used |= outcome.used_registry;
+        response = response
+            .add_submessages(outcome.response.messages)
+            .add_attributes(outcome.response.attributes)
+            .add_attribute("step", step.version);
+    }
+    if matches!(msg, MigrateMsg::Migrate { .. }) && !registry_used {
+        return Err(ContractError::UnexpectedRegistryDetails);
+    }
+
+    STORED_VERSION.save(deps.storage, &ContractVersion {
+        contract: CONTRACT_NAME.to_string(),
+        version: CONTRACT_VERSION.to_string(),
+    })?;
+
+    // A dry run fails on purpose so the chain reverts the steps and keeps the old code
+    if let Some(before) = before {
+        let preview = dry_run_report(deps.storage, stored.version, &steps, before)?;
+        return Err(ContractError::MigrationDryRun {
+            preview: String::from_utf8(to_vec(&preview)?).map_err(StdError::from)?,
+        });
+    }
+
+    Ok(response)
+}
+
+/// What the legacy migration will write, computed from storage without changing it
+pub struct LegacyMigrationPlan {
+    pub old_config: OldConfig,
+    /// Each allocation as stored today and as it will be stored after the migration
+    pub allocations: Vec<(OldAllocation, Allocation)>,
+    pub state: State,
+    /// ERTH the old system owed since its last upkeep, split across allocations by weight
+    pub settled_rewards: Uint128,
+}
+
+/// Compute the legacy migration's outcome at `now`; `migrate_state` writes exactly this plan
+pub fn plan_legacy_migration(storage: &dyn Storage, now: Timestamp) -> StdResult<LegacyMigrationPlan> {
+    let old_config_storage: Item<OldConfig> = Item::new(b"config");
+    let old_config = old_config_storage.load(storage)?;
 
     // Load old state explicitly (without reward_index/epoch fields)
     let old_state_storage: Item<OldState> = Item::new(b"state");
-    let old_state = old_state_storage.load(deps.storage)?;
+    let old_state = old_state_storage.load(storage)?;
 
     // Load old allocation options (without description/last_reward_index fields)
     let old_alloc_storage: Item<Vec<OldAllocation>> = Item::new(b"allocation_options");
-    let old_allocations = old_alloc_storage.load(deps.storage).unwrap_or_default();
+    let old_allocations = old_alloc_storage.load(storage).unwrap_or_default();
 
     // Calculate final reward distribution from old system
-    let time_elapsed = env.block.time.seconds().saturating_sub(old_state.last_upkeep.seconds());
-    let total_rewards = Uint128::from(time_elapsed) * Uint128::from(1_000_000u128);
+    let time_elapsed = now.seconds().saturating_sub(old_state.last_upkeep.seconds());
+    let settled_rewards = Uint128::from(time_elapsed) * Uint128::from(1_000_000u128);
     let old_total_allocations: Uint128 = old_allocations.iter()
         .fold(Uint128::zero(), |acc, a| acc + a.state.amount_allocated);
 
-    // Migrate each allocation to new Keymap storage with new fields
-    let mut ids = Vec::new();
+    let mut allocations = Vec::with_capacity(old_allocations.len());
     for old_alloc in old_allocations {
         let mut accumulated_rewards = old_alloc.state.accumulated_rewards;
 
         // Settle final rewards from old system
         if !old_total_allocations.is_zero() && !old_alloc.state.amount_allocated.is_zero() {
-            let share = old_alloc.state.amount_allocated * total_rewards / old_total_allocations;
+            let share = old_alloc.state.amount_allocated * settled_rewards / old_total_allocations;
             accumulated_rewards = accumulated_rewards + share;
         }
 
+        // Votes don't carry over: every allocation restarts at zero under the reward index
         let new_alloc = Allocation {
             state: AllocationState {
                 allocation_id: old_alloc.state.allocation_id,
@@ -133,33 +250,64 @@ fn migrate_state(
             },
             config: AllocationConfig {
                 description: String::new(),
-                receive_addr: old_alloc.config.receive_addr,
-                receive_hash: old_alloc.config.receive_hash,
-                manager_addr: old_alloc.config.manager_addr,
-                claimer_addr: old_alloc.config.claimer_addr,
+                receive_addr: old_alloc.config.receive_addr.clone(),
+                receive_hash: old_alloc.config.receive_hash.clone(),
+                manager_addr: old_alloc.config.manager_addr.clone(),
+                claimer_addr: old_alloc.config.claimer_addr.clone(),
                 use_send: old_alloc.config.use_send,
             },
         };
-
-        let id = new_alloc.state.allocation_id;
-        ids.push(id);
-        ALLOCATION_OPTIONS.insert(deps.storage, &id, &new_alloc)?;
+        allocations.push((old_alloc, new_alloc));
     }
 
-    ALLOCATION_IDS.save(deps.storage, &ids)?;
-
     // Create new state with reward index fields
-    let new_state = State {
+    let state = State {
         registrations: old_state.registrations,
         last_anml_buyback: old_state.last_anml_buyback,
         total_allocations: Uint128::zero(),
         allocation_counter: old_state.allocation_counter,
         registration_reward: old_state.registration_reward,
-        last_upkeep: env.block.time,
+        last_upkeep: now,
         reward_index: Uint128::zero(),
         epoch: 0,
     };
-    STATE.save(deps.storage, &new_state)?;
+
+    Ok(LegacyMigrationPlan { old_config, allocations, state, settled_rewards })
+}
+
+/// Move the original release onto the registry-based config and keyed allocation storage
+fn migrate_state(
+    deps: DepsMut,
+    env: &Env,
+    msg: &MigrateMsg,
+) -> Result<StepOutcome, ContractError> {
+    let (registry_contract, registry_hash) = match msg {
+        MigrateMsg::Migrate { registry_contract, registry_hash, .. } => (registry_contract, registry_hash),
+        MigrateMsg::Upgrade { .. } => return Err(ContractError::MigrationNeedsRegistry { version: "0.0.1".to_string() }),
+    };
+
+    let plan = plan_legacy_migration(deps.storage, env.block.time)?;
+    let registry_addr = deps.api.addr_validate(registry_contract)?;
+
+    let new_config = Config {
+        registration_address: plan.old_config.registration_address,
+        registration_wallet: plan.old_config.registration_wallet,
+        contract_manager: plan.old_config.contract_manager,
+        registration_validity_seconds: plan.old_config.registration_validity_seconds,
+        registry_contract: registry_addr,
+        registry_hash: registry_hash.clone(),
+    };
+    CONFIG.save(deps.storage, &new_config)?;
+
+    // Migrate each allocation to new Keymap storage with new fields
+    let mut ids = Vec::with_capacity(plan.allocations.len());
+    for (_, new_alloc) in &plan.allocations {
+        let id = new_alloc.state.allocation_id;
+        ids.push(id);
+        ALLOCATION_OPTIONS.insert(deps.storage, &id, new_alloc)?;
+    }
+    ALLOCATION_IDS.save(deps.storage, &ids)?;
+    STATE.save(deps.storage, &plan.state)?;
 
     // Query registry for erth_token to register receiver
     let deps_ref = deps.as_ref();
@@ -182,9 +330,432 @@ fn migrate_state(
         funds: vec![],
     });
 
-    Ok(Response::new()
-        .add_message(register_erth_msg)
-        .add_attribute("action", "migrate")
-        .add_attribute("allocations_migrated", ids.len().to_string())
-        .add_attribute("status", "success"))
+    Ok(StepOutcome {
+        response: Response::new()
+            .add_message(register_erth_msg)
+            .add_attribute("allocations_migrated", ids.len().to_string()),
+        used_registry: true,
+    })
+}
+
+/// Allocations and totals before any step ran, reported against the migrated storage by a dry run
+struct AllocationSnapshot {
+    /// Allocation id, amount allocated and accumulated rewards
+    allocations: Vec<(u32, Uint128, Uint128)>,
+    total_allocations: Uint128,
+    settled_rewards: Uint128,
+}
+
+fn snapshot_allocations(storage: &dyn Storage, legacy: bool, now: Timestamp) -> StdResult<AllocationSnapshot> {
+    if legacy {
+        let plan = plan_legacy_migration(storage, now)?;
+        let allocations: Vec<(u32, Uint128, Uint128)> = plan.allocations.iter()
+            .map(|(old, _)| (old.state.allocation_id, old.state.amount_allocated, old.state.accumulated_rewards))
+            .collect();
+        let total_allocations = allocations.iter().fold(Uint128::zero(), |acc, (_, amount, _)| acc + amount);
+        return Ok(AllocationSnapshot { allocations, total_allocations, settled_rewards: plan.settled_rewards });
+    }
+    let ids = ALLOCATION_IDS.may_load(storage)?.unwrap_or_default();
+    Ok(AllocationSnapshot {
+        allocations: ids.iter()
+            .filter_map(|id| ALLOCATION_OPTIONS.get(storage, id))
+            .map(|a| (a.state.allocation_id, a.state.amount_allocated, a.state.accumulated_rewards))
+            .collect(),
+        total_allocations: STATE.load(storage)?.total_allocations,
+        settled_rewards: Uint128::zero(),
+    })
+}
+
+/// Compare the storage the steps just wrote with the snapshot taken before they ran
+fn dry_run_report(
+    storage: &dyn Storage,
+    from_version: String,
+    steps: &[&MigrationStep],
+    before: AllocationSnapshot,
+) -> StdResult<MigrationPreviewResponse> {
+    let state = STATE.load(storage)?;
+    let ids = ALLOCATION_IDS.may_load(storage)?.unwrap_or_default();
+    let allocations: Vec<Allocation> = ids.iter()
+        .filter_map(|id| ALLOCATION_OPTIONS.get(storage, id))
+        .collect();
+
+    let mut response = MigrationPreviewResponse {
+        from_version,
+        to_version: CONTRACT_VERSION.to_string(),
+        steps: vec![],
+        allocations: vec![],
+        total_allocations_before: before.total_allocations,
+        total_allocations_after: state.total_allocations,
+        accumulated_rewards_before: Uint128::zero(),
+        accumulated_rewards_after: Uint128::zero(),
+        settled_rewards: before.settled_rewards,
+        invariants: allocation_invariants(&allocations, &ids, &state),
+    };
+    for step in steps {
+        if !response.steps.iter().any(|version| version == step.version) {
+            response.steps.push(step.version.to_string());
+        }
+    }
+    for (allocation_id, amount_before, rewards_before) in before.allocations {
+        let after = allocations.iter().find(|a| a.state.allocation_id == allocation_id);
+        let (amount_after, rewards_after) = after
+            .map(|a| (a.state.amount_allocated, a.state.accumulated_rewards))
+            .unwrap_or_default();
+        response.accumulated_rewards_before += rewards_before;
+        response.allocations.push(AllocationMigrationPreview {
+            allocation_id,
+            amount_allocated_before: amount_before,
+            amount_allocated_after: amount_after,
+            accumulated_rewards_before: rewards_before,
+            accumulated_rewards_after: rewards_after,
+        });
+    }
+    response.accumulated_rewards_after = allocations.iter()
+        .fold(Uint128::zero(), |acc, a| acc + a.state.accumulated_rewards);
+
+    Ok(response)
+}
+
+/// Settle `allocations` at the reward index of `state`, then scale their tallies and the total
+fn rescale_allocations(allocations: &mut [Allocation], state: &mut State) -> Result<(), ContractError> {
+    for allocation in allocations.iter_mut() {
+        settle_allocation(&mut allocation.state, state.reward_index);
+        allocation.state.amount_allocated = allocation.state.amount_allocated
+            .checked_mul(Uint128::from(TALLY_RESCALE))
+            .map_err(|_| ContractError::Overflow("amount allocated"))?;
+    }
+    state.total_allocations = state.total_allocations
+        .checked_mul(Uint128::from(TALLY_RESCALE))
+        .map_err(|_| ContractError::Overflow("total allocations"))?;
+    Ok(())
+}
+
+/// Settle every allocation at the current reward index, then scale the tallies into the
+/// units votes are now counted in so existing votes can still be withdrawn exactly
+fn rescale_tallies(
+    deps: DepsMut,
+    env: &Env,
+    _msg: &MigrateMsg,
+) -> Result<StepOutcome, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    update_reward_indexes(deps.storage, &mut state, env.block.time)?;
+
+    let ids = ALLOCATION_IDS.may_load(deps.storage)?.unwrap_or_default();
+    let mut allocations: Vec<Allocation> = ids.iter()
+        .filter_map(|id| ALLOCATION_OPTIONS.get(deps.storage, id))
+        .collect();
+    rescale_allocations(&mut allocations, &mut state)?;
+    for allocation in &allocations {
+        ALLOCATION_OPTIONS.insert(deps.storage, &allocation.state.allocation_id, allocation)?;
+    }
+    STATE.save(deps.storage, &state)?;
+
+    Ok(StepOutcome {
+        response: Response::new()
+            .add_attribute("tallies_rescaled", ids.len().to_string()),
+        used_registry: false,
+    })
+}
+
+/// Votes from before 0.1.0 aren't in the expiry queue; have the expiry processing queue them
+/// a page at a time, as the keeper or other calls drive it
+fn schedule_vote_expiry_backfill(
+    deps: DepsMut,
+    _env: &Env,
+    _msg: &MigrateMsg,
+) -> Result<StepOutcome, ContractError> {
+    VOTE_EXPIRY_BACKFILL.save(deps.storage, &0)?;
+
+    Ok(StepOutcome {
+        response: Response::new()
+            .add_attribute("vote_expiry_backfill", "scheduled"),
+        used_registry: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_slice, ContractResult, OwnedDeps, SystemResult, WasmQuery};
+    use crate::state::config::{AllContractsResponse, ContractResponse};
+
+    const ELAPSED: u64 = 10;
+
+    fn registry_deps() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| {
+            let response = AllContractsResponse {
+                contracts: vec![ContractResponse {
+                    name: "erth_token".to_string(),
+                    info: crate::state::ContractInfo {
+                        address: Addr::unchecked("erth"),
+                        code_hash: "erth_hash".to_string(),
+                    },
+                }],
+            };
+            match query {
+                WasmQuery::Smart { contract_addr, .. } if contract_addr == "registry" => {
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&response).unwrap()))
+                }
+                _ => panic!("unexpected query {:?}", query),
+            }
+        });
+        deps
+    }
+
+    fn migrate_msg(dry_run: bool) -> MigrateMsg {
+        MigrateMsg::Migrate {
+            registry_contract: "registry".to_string(),
+            registry_hash: "registry_hash".to_string(),
+            dry_run,
+        }
+    }
+
+    /// Storage as the original release wrote it
+    fn save_legacy_fixture(storage: &mut dyn Storage, now: Timestamp) {
+        Item::<OldConfig>::new(b"config").save(storage, &OldConfig {
+            registration_address: Addr::unchecked("registrar"),
+            registration_wallet: Addr::unchecked("wallet"),
+            contract_manager: Addr::unchecked("manager"),
+            registration_validity_seconds: 86_400,
+            anml_token_contract: Addr::unchecked("anml"),
+            anml_token_hash: "anml_hash".to_string(),
+            erth_token_contract: Addr::unchecked("erth"),
+            erth_token_hash: "erth_hash".to_string(),
+            anml_pool_contract: Addr::unchecked("pool"),
+            anml_pool_hash: "pool_hash".to_string(),
+        }).unwrap();
+        Item::<OldState>::new(b"state").save(storage, &OldState {
+            registrations: 7,
+            last_anml_buyback: now.minus_seconds(100),
+            total_allocations: Uint128::new(400),
+            allocation_counter: 2,
+            registration_reward: Uint128::new(1_000),
+            last_upkeep: now.minus_seconds(ELAPSED),
+        }).unwrap();
+        let old_allocation = |allocation_id, amount_allocated, accumulated_rewards| OldAllocation {
+            state: OldAllocationState {
+                allocation_id,
+                amount_allocated: Uint128::new(amount_allocated),
+                last_claim: now.minus_seconds(1_000),
+                accumulated_rewards: Uint128::new(accumulated_rewards),
+            },
+            config: OldAllocationConfig {
+                receive_addr: Addr::unchecked(format!("receiver{}", allocation_id)),
+                receive_hash: None,
+                manager_addr: Some(Addr::unchecked("manager")),
+                claimer_addr: None,
+                use_send: false,
+            },
+        };
+        Item::<Vec<OldAllocation>>::new(b"allocation_options")
+            .save(storage, &vec![old_allocation(1, 300, 5), old_allocation(2, 100, 0)])
+            .unwrap();
+    }
+
+    /// Storage as 0.0.2 wrote it: current layout, no stored version, tallies in whole percent
+    fn save_v002_fixture(storage: &mut dyn Storage, now: Timestamp) {
+        STATE.save(storage, &State {
+            registrations: 7,
+            last_anml_buyback: now.minus_seconds(100),
+            total_allocations: Uint128::new(150),
+            allocation_counter: 2,
+            registration_reward: Uint128::new(1_000),
+            last_upkeep: now.minus_seconds(ELAPSED),
+            reward_index: Uint128::zero(),
+            epoch: 0,
+        }).unwrap();
+        for (allocation_id, amount_allocated) in [(1u32, 100u128), (2, 50)] {
+            ALLOCATION_OPTIONS.insert(storage, &allocation_id, &Allocation {
+                state: AllocationState {
+                    allocation_id,
+                    amount_allocated: Uint128::new(amount_allocated),
+                    last_claim: now.minus_seconds(1_000),
+                    accumulated_rewards: Uint128::zero(),
+                    last_reward_index: Uint128::zero(),
+                },
+                config: AllocationConfig {
+                    description: String::new(),
+                    receive_addr: Addr::unchecked(format!("receiver{}", allocation_id)),
+                    receive_hash: None,
+                    manager_addr: None,
+                    claimer_addr: None,
+                    use_send: false,
+                },
+            }).unwrap();
+        }
+        ALLOCATION_IDS.save(storage, &vec![1, 2]).unwrap();
+    }
+
+    fn save_stored_version(storage: &mut dyn Storage, contract: &str, version: &str) {
+        STORED_VERSION.save(storage, &ContractVersion {
+            contract: contract.to_string(),
+            version: version.to_string(),
+        }).unwrap();
+    }
+
+    fn allocation(storage: &dyn Storage, allocation_id: u32) -> AllocationState {
+        ALLOCATION_OPTIONS.get(storage, &allocation_id).unwrap().state
+    }
+
+    #[test]
+    fn migrate_from_original_release() {
+        let mut deps = registry_deps();
+        let env = mock_env();
+        save_legacy_fixture(deps.as_mut().storage, env.block.time);
+        assert_eq!(stored_version(deps.as_ref().storage).unwrap().version, "0.0.1");
+
+        let response = perform_migration(deps.as_mut(), env.clone(), migrate_msg(false)).unwrap();
+        assert_eq!(response.messages.len(), 1);
+        match &response.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, code_hash, .. }) => {
+                assert_eq!(contract_addr, "erth");
+                assert_eq!(code_hash, "erth_hash");
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.contract_manager, Addr::unchecked("manager"));
+        assert_eq!(config.registry_contract, Addr::unchecked("registry"));
+        assert_eq!(config.registry_hash, "registry_hash");
+
+        // 10 seconds of the old emission are split 3:1 and votes restart at zero
+        assert_eq!(ALLOCATION_IDS.load(deps.as_ref().storage).unwrap(), vec![1, 2]);
+        let first = allocation(deps.as_ref().storage, 1);
+        assert_eq!(first.amount_allocated, Uint128::zero());
+        assert_eq!(first.accumulated_rewards, Uint128::new(7_500_005));
+        assert_eq!(allocation(deps.as_ref().storage, 2).accumulated_rewards, Uint128::new(2_500_000));
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.registrations, 7);
+        assert_eq!(state.total_allocations, Uint128::zero());
+        assert_eq!(state.last_upkeep, env.block.time);
+        assert_eq!(stored_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn upgrade_from_untracked_release() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        save_v002_fixture(deps.as_mut().storage, env.block.time);
+        assert_eq!(stored_version(deps.as_ref().storage).unwrap().version, "0.0.2");
+
+        let response = perform_migration(deps.as_mut(), env.clone(), MigrateMsg::Upgrade { dry_run: false }).unwrap();
+        assert!(response.messages.is_empty());
+
+        // Rewards up to now are settled at the old tallies before they are scaled
+        let first = allocation(deps.as_ref().storage, 1);
+        let second = allocation(deps.as_ref().storage, 2);
+        assert_eq!(first.amount_allocated, Uint128::new(10_000));
+        assert_eq!(second.amount_allocated, Uint128::new(5_000));
+        assert_eq!(first.accumulated_rewards, Uint128::new(6_666_666));
+        assert_eq!(second.accumulated_rewards, Uint128::new(3_333_333));
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.total_allocations, Uint128::new(15_000));
+        assert_eq!(state.last_upkeep, env.block.time);
+        assert_eq!(first.last_reward_index, state.reward_index);
+        assert_eq!(stored_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn tallies_are_rescaled_exactly_once() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        save_v002_fixture(deps.as_mut().storage, env.block.time);
+        save_stored_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.9");
+
+        let response = perform_migration(deps.as_mut(), env.clone(), MigrateMsg::Upgrade { dry_run: false }).unwrap();
+        assert!(response.attributes.iter().any(|attr| attr.key == "step" && attr.value == RESCALE_STEP_VERSION));
+        assert_eq!(allocation(deps.as_ref().storage, 1).amount_allocated, Uint128::new(100 * 100));
+        assert_eq!(allocation(deps.as_ref().storage, 2).amount_allocated, Uint128::new(50 * 100));
+        assert_eq!(STATE.load(deps.as_ref().storage).unwrap().total_allocations, Uint128::new(150 * 100));
+
+        // Storage at 0.1.0 has nothing left to rescale
+        let response = perform_migration(deps.as_mut(), env, MigrateMsg::Upgrade { dry_run: false }).unwrap();
+        assert!(!response.attributes.iter().any(|attr| attr.key == "step"));
+        assert_eq!(allocation(deps.as_ref().storage, 1).amount_allocated, Uint128::new(100 * 100));
+        assert_eq!(STATE.load(deps.as_ref().storage).unwrap().total_allocations, Uint128::new(150 * 100));
+    }
+
+    #[test]
+    fn dry_run_fails_with_the_real_outcome() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        save_v002_fixture(deps.as_mut().storage, env.block.time);
+
+        // The error reverts the upgrade on chain and carries what the steps did
+        let err = perform_migration(deps.as_mut(), env, MigrateMsg::Upgrade { dry_run: true }).unwrap_err();
+        assert_eq!(err.code(), 1005);
+        let preview: MigrationPreviewResponse = match err {
+            ContractError::MigrationDryRun { preview } => from_slice(preview.as_bytes()).unwrap(),
+            err => panic!("unexpected error {:?}", err),
+        };
+        assert_eq!(preview.steps, vec![RESCALE_STEP_VERSION.to_string()]);
+        assert_eq!(preview.total_allocations_before, Uint128::new(150));
+        assert_eq!(preview.total_allocations_after, Uint128::new(15_000));
+        assert_eq!(preview.allocations[0].amount_allocated_after, Uint128::new(10_000));
+        assert_eq!(preview.allocations[0].accumulated_rewards_after, Uint128::new(6_666_666));
+        assert_eq!(preview.accumulated_rewards_after, Uint128::new(9_999_999));
+        assert!(preview.invariants.iter().all(|check| check.holds));
+    }
+
+    #[test]
+    fn legacy_dry_run_reports_settled_rewards() {
+        let mut deps = registry_deps();
+        let env = mock_env();
+        save_legacy_fixture(deps.as_mut().storage, env.block.time);
+
+        let err = perform_migration(deps.as_mut(), env, migrate_msg(true)).unwrap_err();
+        let preview: MigrationPreviewResponse = match err {
+            ContractError::MigrationDryRun { preview } => from_slice(preview.as_bytes()).unwrap(),
+            err => panic!("unexpected error {:?}", err),
+        };
+        assert_eq!(preview.from_version, "0.0.1");
+        assert_eq!(preview.settled_rewards, Uint128::new(10_000_000));
+        assert_eq!(preview.total_allocations_before, Uint128::new(400));
+        assert_eq!(preview.total_allocations_after, Uint128::zero());
+        assert_eq!(preview.accumulated_rewards_before, Uint128::new(5));
+        assert_eq!(preview.accumulated_rewards_after, Uint128::new(10_000_005));
+    }
+
+    #[test]
+    fn registry_details_must_match_the_stored_version() {
+        let mut deps = registry_deps();
+        let env = mock_env();
+        save_legacy_fixture(deps.as_mut().storage, env.block.time);
+        let err = perform_migration(deps.as_mut(), env.clone(), MigrateMsg::Upgrade { dry_run: false }).unwrap_err();
+        assert_eq!(err, ContractError::MigrationNeedsRegistry { version: "0.0.1".to_string() });
+
+        let mut deps = registry_deps();
+        save_v002_fixture(deps.as_mut().storage, env.block.time);
+        let err = perform_migration(deps.as_mut(), env, migrate_msg(false)).unwrap_err();
+        assert_eq!(err, ContractError::UnexpectedRegistryDetails);
+    }
+
+    #[test]
+    fn rejects_downgrade() {
+        let mut deps = mock_dependencies();
+        save_stored_version(deps.as_mut().storage, CONTRACT_NAME, "9.9.9");
+        let err = perform_migration(deps.as_mut(), mock_env(), MigrateMsg::Upgrade { dry_run: false }).unwrap_err();
+        assert_eq!(err, ContractError::MigrationDowngrade {
+            stored: "9.9.9".to_string(),
+            current: CONTRACT_VERSION.to_string(),
+        });
+        assert_eq!(err.code(), 1002);
+    }
+
+    #[test]
+    fn rejects_other_contract() {
+        let mut deps = mock_dependencies();
+        save_stored_version(deps.as_mut().storage, "other-contract", "0.0.1");
+        let err = perform_migration(deps.as_mut(), mock_env(), MigrateMsg::Upgrade { dry_run: true }).unwrap_err();
+        assert_eq!(err, ContractError::WrongContract {
+            stored: "other-contract".to_string(),
+            current: CONTRACT_NAME.to_string(),
+        });
+        assert_eq!(err.code(), 1000);
+    }
 }
@@ -2,7 +2,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cosmwasm_std::{Addr, Binary, Uint128, Timestamp};
-use crate::state::{AllocationConfig, AllocationPercentage, Config,};
+use crate::state::{AllocationConfig, AllocationPercentage, VotingConfig, EpochVotingRules, Delegation,
+    ProposalConfig, AllocationRecipient, VoterRewardConfig,
+    PendingReceiveChange, PendingRecipientsChange, Role, PauseScope, ContractInfo, CachedContract};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -17,9 +19,12 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    UpdateConfig {
-        config: Config,
+    UpdateConfig(ConfigUpdate),
+    ProposeManager {
+        address: String,
     },
+    AcceptManager {},
+    CancelManagerProposal {},
     Register {
         address: String,
         id_hash: String,
@@ -33,18 +38,120 @@ pub enum ExecuteMsg {
         allocation_id: u32,
     },
     AddAllocation {
-        description: String,
-        receive_addr: Addr,
-        receive_hash: Option<String>,
-        manager_addr: Option<Addr>,
-        claimer_addr: Option<Addr>,
-        use_send: bool,
+        config: AllocationConfig,
     },
     EditAllocation {
         allocation_id: u32,
         config: AllocationConfig,
     },
     ResetAllocations {},
+    UpdateVotingConfig {
+        config: VotingConfig,
+    },
+    RefreshAllocationWeight {
+        address: String,
+    },
+    SetEpochVotingRules {
+        epoch: u32,
+        rules: EpochVotingRules,
+    },
+    ProcessVoteExpiries {
+        limit: Option<u32>,
+    },
+    DelegateAllocation {
+        delegate: String,
+    },
+    UndelegateAllocation {},
+    SetAutoRenew {
+        enabled: bool,
+    },
+    SkipAutoRenew {
+        epoch: Option<u32>,
+    },
+    ProcessAutoRenewals {},
+    EndorseProposal {
+        proposal_id: u32,
+    },
+    ApproveProposal {
+        proposal_id: u32,
+    },
+    RejectProposal {
+        proposal_id: u32,
+        slash: bool,
+    },
+    SetProposalConfig {
+        config: ProposalConfig,
+    },
+    SetAllocationRecipients {
+        allocation_id: u32,
+        recipients: Vec<AllocationRecipient>,
+    },
+    SetAllocationVesting {
+        allocation_id: u32,
+        duration_seconds: Option<u64>,
+    },
+    WithdrawVested {
+        allocation_id: u32,
+    },
+    RegisterRewardToken {
+        token: String,
+        code_hash: String,
+    },
+    ClaimAllocationToken {
+        allocation_id: u32,
+        token: String,
+    },
+    ClaimVoterTokenRewards {
+        token: String,
+    },
+    ProposeAllocationManager {
+        allocation_id: u32,
+        manager: String,
+    },
+    AcceptAllocationManager {
+        allocation_id: u32,
+    },
+    ProposeAllocationClaimer {
+        allocation_id: u32,
+        claimer: Option<String>,
+    },
+    AcceptAllocationClaimer {
+        allocation_id: u32,
+    },
+    ClaimVoterRewards {},
+    SetVoterRewardConfig {
+        config: VoterRewardConfig,
+    },
+    SetAdminTimelock {
+        delay_seconds: u64,
+    },
+    QueueAdminAction {
+        action: AdminAction,
+    },
+    ExecuteAdminAction {
+        id: u32,
+    },
+    CancelAdminAction {
+        id: u32,
+    },
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+    SetPause {
+        scope: PauseScope,
+        paused: bool,
+    },
+    RefreshRegistry {},
+    RepairTotals {},
+    SetRegistryPin {
+        name: String,
+        contract: Option<ContractInfo>,
+    },
     Receive {
         sender: Addr,
         from: Addr,
@@ -54,12 +161,110 @@ pub enum ExecuteMsg {
     },
 }
 
+/// Manager actions that go through the admin timelock while it is enabled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    UpdateConfig(ConfigUpdate),
+    AddAllocation {
+        config: AllocationConfig,
+    },
+    EditAllocation {
+        allocation_id: u32,
+        config: AllocationConfig,
+    },
+    ResetAllocations {},
+    SetAdminTimelock {
+        delay_seconds: u64,
+    },
+    ProposeManager {
+        address: String,
+    },
+    ApproveProposal {
+        proposal_id: u32,
+    },
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+    SetRegistryPin {
+        name: String,
+        contract: Option<ContractInfo>,
+    },
+    SetAllocationVesting {
+        allocation_id: u32,
+        duration_seconds: Option<u64>,
+    },
+    UpdateVotingConfig {
+        config: VotingConfig,
+    },
+    SetEpochVotingRules {
+        epoch: u32,
+        rules: EpochVotingRules,
+    },
+    SetVoterRewardConfig {
+        config: VoterRewardConfig,
+    },
+    RegisterRewardToken {
+        token: String,
+        code_hash: String,
+    },
+    SetProposalConfig {
+        config: ProposalConfig,
+    },
+    RejectProposal {
+        proposal_id: u32,
+        slash: bool,
+    },
+    SetAllocationRecipients {
+        allocation_id: u32,
+        recipients: Vec<AllocationRecipient>,
+    },
+    ProposeAllocationManager {
+        allocation_id: u32,
+        manager: String,
+    },
+    ProposeAllocationClaimer {
+        allocation_id: u32,
+        claimer: Option<String>,
+    },
+}
+
+/// Config fields to change; omitted fields keep their value
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    pub registration_address: Option<String>,
+    pub registration_wallet: Option<String>,
+    pub registration_validity_seconds: Option<u64>,
+    pub registry_contract: Option<String>,
+    pub registry_hash: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
     AllocationSend {
         allocation_id: u32,
-    }
+    },
+    ProposeAllocation(ProposeAllocationMsg),
+    FundRegistrationPool {},
+    FundRewardStream {
+        duration_seconds: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ProposeAllocationMsg {
+    pub description: String,
+    pub receive_addr: String,
+    pub receive_hash: Option<String>,
+    pub manager_addr: Option<String>,
+    pub claimer_addr: Option<String>,
+    pub use_send: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -77,11 +282,22 @@ pub enum SendMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MigrateMsg {
+    /// Upgrade an original-release deployment, which predates the registry
     Migrate {
         registry_contract: String,
         registry_hash: String,
+        /// Run the migration, then fail with `MigrationDryRun` carrying the
+        /// `MigrationPreviewResponse` as JSON so the upgrade reverts; meant for simulating
+        /// the migrate transaction
+        #[serde(default)]
+        dry_run: bool,
+    },
+    /// Upgrade from any tracked version
+    Upgrade {
+        /// As for `Migrate`
+        #[serde(default)]
+        dry_run: bool,
     },
-    Upgrade {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -93,6 +309,33 @@ pub enum QueryMsg {
     QueryRegistrationStatusByIdHash { id_hash: String },
     QueryUserAllocations { address: String },
     QueryAllocationOptions {},
+    QueryVotingConfig {},
+    QueryUserVote { address: String },
+    QueryEpochVotingRules { epoch: Option<u32> },
+    QueryDelegation { address: String },
+    QueryEpochResults { epoch: u32 },
+    QueryEpochList { start_after: Option<u32>, limit: Option<u32> },
+    QueryAutoRenew { address: String },
+    QueryProposalConfig {},
+    QueryProposal { proposal_id: u32 },
+    QueryProposals { start_after: Option<u32>, limit: Option<u32> },
+    QueryAllocationRecipients { allocation_id: u32 },
+    QueryVesting { allocation_id: u32 },
+    QueryBonusPool { allocation_id: u32 },
+    QueryRewardStreams {},
+    QueryAllocationTokenRewards { allocation_id: u32 },
+    QueryVoterRewardConfig {},
+    QueryVoterRewards { address: String },
+    QueryVoterTokenRewards { address: String },
+    QueryPendingAllocationChanges { allocation_id: u32 },
+    QueryPendingManager {},
+    QueryAdminTimelock {},
+    QueryPendingAdminActions { start_after: Option<u32>, limit: Option<u32> },
+    QueryRoles { address: String },
+    QueryPauseStatus {},
+    QueryRegistryCache {},
+    QueryContractVersion {},
+    QueryCheckInvariants { scope: InvariantScope, page: Option<u32>, limit: Option<u32> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -114,3 +357,114 @@ pub struct StateResponse {
     pub reward_index: Uint128,
     pub epoch: u32,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub delegation: Option<Delegation>,
+    pub delegated_weight: Uint128, // Weight flowing in from this address's delegators
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EpochSummary {
+    pub epoch: u32,
+    pub closed_at: Timestamp,
+    pub total_allocations: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct VestingResponse {
+    pub duration_seconds: Option<u64>, // None when claims pay out immediately
+    pub locked: Uint128,
+    pub withdrawable: Uint128, // Vested as of the query time
+    pub vesting_end: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct TokenRewardResponse {
+    pub token: Addr,
+    pub pending: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct VoterRewardResponse {
+    pub epoch: u32,
+    pub weight: Uint128, // Weight earning in the current epoch
+    pub pending: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub granted: Vec<Role>, // Roles stored for the address
+    pub effective: Vec<Role>, // Roles it passes checks for, including implied ones
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PendingAllocationChangesResponse {
+    pub pending_manager: Option<Addr>,
+    pub pending_claimer: Option<Addr>,
+    pub pending_receive: Option<PendingReceiveChange>,
+    pub pending_recipients: Option<PendingRecipientsChange>,
+    pub pending_claimer_removal: Option<Timestamp>, // When removing the claimer takes effect
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RegistryCacheEntry {
+    pub name: String,
+    pub cached: Option<CachedContract>,
+    pub stale: bool, // Cached entry is past its TTL and will be re-queried on next use
+    pub pinned: Option<ContractInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MigrationPreviewResponse {
+    pub from_version: String,
+    pub to_version: String,
+    pub steps: Vec<String>, // Migration steps that would run, by target version
+    pub allocations: Vec<AllocationMigrationPreview>, // Each allocation before and after the steps
+    pub total_allocations_before: Uint128,
+    pub total_allocations_after: Uint128,
+    pub accumulated_rewards_before: Uint128,
+    pub accumulated_rewards_after: Uint128,
+    pub settled_rewards: Uint128, // Owed by the old reward system and credited during migration
+    pub invariants: Vec<InvariantCheck>, // Checked against the migrated storage
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AllocationMigrationPreview {
+    pub allocation_id: u32,
+    pub amount_allocated_before: Uint128,
+    pub amount_allocated_after: Uint128,
+    pub accumulated_rewards_before: Uint128,
+    pub accumulated_rewards_after: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct InvariantCheck {
+    pub name: String,
+    pub holds: bool,
+}
+
+/// Group of invariants checked by `QueryCheckInvariants`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InvariantScope {
+    Totals, // Allocation list against `State`; not paginated
+    UserAllocations,
+    Registrations, // Registrations by address against by hash
+    RegistrationHashes, // Registrations by hash against by address
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct InvariantViolation {
+    pub invariant: String,
+    pub key: Option<String>, // Offending address or ID hash; `None` for totals
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct InvariantReport {
+    pub scope: InvariantScope,
+    pub page: u32,
+    pub checked: u32, // Entries examined on this page
+    pub more: bool, // Further pages remain in this scope
+    pub violations: Vec<InvariantViolation>,
+}
@@ -1,9 +1,32 @@
 // src/query/mod.rs
-use cosmwasm_std::{Deps, Env, Binary, StdResult, to_binary, Timestamp,};
-use crate::msg::{QueryMsg, RegistrationStatusResponse, StateResponse};
+use cosmwasm_std::{Deps, Env, Binary, StdError, StdResult, to_binary, Timestamp, Uint128};
+use crate::msg::{QueryMsg, RegistrationStatusResponse, StateResponse, DelegationResponse, EpochSummary,
+    VestingResponse, TokenRewardResponse, VoterRewardResponse,
+    PendingAllocationChangesResponse, RolesResponse, RegistryCacheEntry};
 use crate::state::{USER_ALLOCATIONS, AllocationPercentage, ALLOCATION_OPTIONS, ALLOCATION_IDS, Allocation,
-    STATE, Config, CONFIG, REGISTRATIONS, Registration, NEW_REGISTRATIONS_COUNT};
+    STATE, Config, CONFIG, REGISTRATIONS, Registration, NEW_REGISTRATIONS_COUNT, VotingConfig, VOTING_CONFIG, UserVote, USER_VOTES,
+    EpochVotingRules, DELEGATIONS, EpochResults, EPOCH_RESULTS,
+    AutoRenew, AUTO_RENEW, PROPOSAL_CONFIG, AllocationProposal, PROPOSALS, PROPOSAL_COUNTER, AllocationRecipient,
+    ALLOCATION_VESTING_DURATION, ALLOCATION_VESTING, ALLOCATION_BONUS_POOL,
+    RewardStream, VOTER_REWARD_CONFIG, VOTER_REWARD_POOL,
+    PENDING_ALLOCATION_MANAGERS, PENDING_ALLOCATION_CLAIMERS, PENDING_RECEIVE_CHANGES, PENDING_RECIPIENTS_CHANGES,
+    PENDING_CLAIMER_REMOVALS,
+    PENDING_MANAGER, QueuedAdminAction, ADMIN_TIMELOCK_SECONDS, ADMIN_ACTIONS, ADMIN_ACTION_COUNTER,
+    Role, ROLES, REGISTRY_CONTRACT_NAMES, REGISTRY_CACHE, REGISTRY_PINS, REGISTRY_CACHE_TTL_SECONDS};
+use crate::execute::allocation::{load_user_votes, epoch_voting_rules, INDEX_PRECISION, REWARD_RATE};
+use crate::execute::delegation::delegated_weight;
+use crate::execute::recipients::allocation_recipients;
+use crate::execute::vesting::release_vested;
+use crate::execute::voter_reward::{voter_reward_bps, pending_voter_rewards};
+use crate::execute::roles::has_role;
+use crate::execute::pause::pause_status;
+use crate::execute::invariants::check_invariants;
+use crate::migrate::stored_version;
+use crate::execute::reward_stream::{reward_streams, update_stream_index, pending_token_rewards,
+    pending_voter_token_rewards};
 
+const DEFAULT_PAGE_LIMIT: u32 = 10;
+const MAX_PAGE_LIMIT: u32 = 30;
 
 pub fn query_dispatch(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -13,6 +36,41 @@ pub fn query_dispatch(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary>
         QueryMsg::QueryRegistrationStatusByIdHash { id_hash } => to_binary(&query_registration_status_by_id_hash(deps, env, id_hash)?),
         QueryMsg::QueryAllocationOptions {} => to_binary(&query_allocation_options(deps)?),
         QueryMsg::QueryUserAllocations{address} => to_binary(&query_user_allocations(deps, address)?),
+        QueryMsg::QueryVotingConfig {} => to_binary(&query_voting_config(deps)?),
+        QueryMsg::QueryUserVote { address } => to_binary(&query_user_vote(deps, address)?),
+        QueryMsg::QueryEpochVotingRules { epoch } => to_binary(&query_epoch_voting_rules(deps, epoch)?),
+        QueryMsg::QueryDelegation { address } => to_binary(&query_delegation(deps, address)?),
+        QueryMsg::QueryEpochResults { epoch } => to_binary(&query_epoch_results(deps, epoch)?),
+        QueryMsg::QueryEpochList { start_after, limit } => to_binary(&query_epoch_list(deps, start_after, limit)?),
+        QueryMsg::QueryAutoRenew { address } => to_binary(&query_auto_renew(deps, address)?),
+        QueryMsg::QueryProposalConfig {} => to_binary(&PROPOSAL_CONFIG.may_load(deps.storage)?),
+        QueryMsg::QueryProposal { proposal_id } => to_binary(&query_proposal(deps, proposal_id)?),
+        QueryMsg::QueryProposals { start_after, limit } => to_binary(&query_proposals(deps, start_after, limit)?),
+        QueryMsg::QueryAllocationRecipients { allocation_id } => to_binary(&query_allocation_recipients(deps, allocation_id)?),
+        QueryMsg::QueryVesting { allocation_id } => to_binary(&query_vesting(deps, env, allocation_id)?),
+        QueryMsg::QueryRewardStreams {} => to_binary(&query_reward_streams(deps, env)?),
+        QueryMsg::QueryAllocationTokenRewards { allocation_id } =>
+            to_binary(&query_allocation_token_rewards(deps, env, allocation_id)?),
+        QueryMsg::QueryVoterRewardConfig {} =>
+            to_binary(&VOTER_REWARD_CONFIG.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::QueryVoterRewards { address } => to_binary(&query_voter_rewards(deps, env, address)?),
+        QueryMsg::QueryVoterTokenRewards { address } => to_binary(&query_voter_token_rewards(deps, env, address)?),
+        QueryMsg::QueryPendingAllocationChanges { allocation_id } =>
+            to_binary(&query_pending_allocation_changes(deps, allocation_id)?),
+        QueryMsg::QueryPendingManager {} => to_binary(&PENDING_MANAGER.may_load(deps.storage)?),
+        QueryMsg::QueryAdminTimelock {} => to_binary(&ADMIN_TIMELOCK_SECONDS.may_load(deps.storage)?.unwrap_or(0)),
+        QueryMsg::QueryPendingAdminActions { start_after, limit } =>
+            to_binary(&query_pending_admin_actions(deps, start_after, limit)?),
+        QueryMsg::QueryRoles { address } => to_binary(&query_roles(deps, address)?),
+        QueryMsg::QueryPauseStatus {} => to_binary(&pause_status(deps.storage)?),
+        QueryMsg::QueryRegistryCache {} => to_binary(&query_registry_cache(deps, env)?),
+        QueryMsg::QueryContractVersion {} => to_binary(&stored_version(deps.storage)?),
+        QueryMsg::QueryCheckInvariants { scope, page, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+            to_binary(&check_invariants(deps, scope, page.unwrap_or(0), limit)?)
+        }
+        QueryMsg::QueryBonusPool { allocation_id } =>
+            to_binary(&ALLOCATION_BONUS_POOL.get(deps.storage, &allocation_id).unwrap_or_default()),
     }
 }
 
@@ -121,3 +179,237 @@ pub fn query_user_allocations(deps: Deps, address: String) -> StdResult<Vec<Allo
         Ok(vec![])
     }
 }
+
+fn query_voting_config(deps: Deps) -> StdResult<VotingConfig> {
+    Ok(VOTING_CONFIG.may_load(deps.storage)?.unwrap_or_default())
+}
+
+/// Weight and expiry of the address's current-epoch vote (zero weight if they haven't voted)
+pub fn query_user_vote(deps: Deps, address: String) -> StdResult<UserVote> {
+    let addr = deps.api.addr_validate(&address)?;
+    let state = STATE.load(deps.storage)?;
+    let (_, weight) = load_user_votes(deps.storage, &addr, state.epoch);
+    let expires = USER_VOTES.get(deps.storage, &addr)
+        .filter(|vote| vote.epoch == state.epoch)
+        .map(|vote| vote.expires)
+        .unwrap_or_default();
+    Ok(UserVote {
+        epoch: state.epoch,
+        weight,
+        expires,
+    })
+}
+
+/// Voting rules for the given epoch, defaulting to the current one
+fn query_epoch_voting_rules(deps: Deps, epoch: Option<u32>) -> StdResult<EpochVotingRules> {
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => STATE.load(deps.storage)?.epoch,
+    };
+    Ok(epoch_voting_rules(deps.storage, epoch))
+}
+
+fn query_delegation(deps: Deps, address: String) -> StdResult<DelegationResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(DelegationResponse {
+        delegation: DELEGATIONS.get(deps.storage, &addr),
+        delegated_weight: delegated_weight(deps.storage, &addr),
+    })
+}
+
+fn query_epoch_results(deps: Deps, epoch: u32) -> StdResult<EpochResults> {
+    EPOCH_RESULTS.get(deps.storage, &epoch)
+        .ok_or_else(|| StdError::generic_err("No results recorded for this epoch"))
+}
+
+/// Closed epochs in ascending order; epochs closed before snapshots existed are skipped
+fn query_epoch_list(deps: Deps, start_after: Option<u32>, limit: Option<u32>) -> StdResult<Vec<EpochSummary>> {
+    let state = STATE.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.map(|epoch| epoch + 1).unwrap_or(0);
+
+    let mut epochs = Vec::new();
+    for epoch in start..state.epoch {
+        if epochs.len() >= limit {
+            break;
+        }
+        if let Some(results) = EPOCH_RESULTS.get(deps.storage, &epoch) {
+            epochs.push(EpochSummary {
+                epoch: results.epoch,
+                closed_at: results.closed_at,
+                total_allocations: results.total_allocations,
+            });
+        }
+    }
+    Ok(epochs)
+}
+
+/// Auto-renew settings of an address, or `None` if it isn't enabled
+fn query_auto_renew(deps: Deps, address: String) -> StdResult<Option<AutoRenew>> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(AUTO_RENEW.get(deps.storage, &addr))
+}
+
+fn query_proposal(deps: Deps, proposal_id: u32) -> StdResult<AllocationProposal> {
+    PROPOSALS.get(deps.storage, &proposal_id)
+        .ok_or_else(|| StdError::generic_err("Proposal not found"))
+}
+
+/// Proposals in ascending id order
+fn query_proposals(deps: Deps, start_after: Option<u32>, limit: Option<u32>) -> StdResult<Vec<AllocationProposal>> {
+    let counter = PROPOSAL_COUNTER.may_load(deps.storage)?.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.unwrap_or(0) + 1;
+
+    Ok((start..=counter)
+        .filter_map(|proposal_id| PROPOSALS.get(deps.storage, &proposal_id))
+        .take(limit)
+        .collect())
+}
+
+/// Resolved payees of an allocation, including the `receive_addr` fallback
+fn query_allocation_recipients(deps: Deps, allocation_id: u32) -> StdResult<Vec<AllocationRecipient>> {
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or_else(|| StdError::generic_err("Allocation not found"))?;
+    Ok(allocation_recipients(deps.storage, &allocation))
+}
+
+fn query_vesting(deps: Deps, env: Env, allocation_id: u32) -> StdResult<VestingResponse> {
+    if !ALLOCATION_OPTIONS.contains(deps.storage, &allocation_id) {
+        return Err(StdError::generic_err("Allocation not found"));
+    }
+
+    let duration_seconds = ALLOCATION_VESTING_DURATION.get(deps.storage, &allocation_id);
+    match ALLOCATION_VESTING.get(deps.storage, &allocation_id) {
+        Some(mut schedule) => {
+            release_vested(&mut schedule, env.block.time);
+            Ok(VestingResponse {
+                duration_seconds,
+                locked: schedule.locked(),
+                withdrawable: schedule.withdrawable,
+                vesting_end: schedule.end(),
+            })
+        }
+        None => Ok(VestingResponse {
+            duration_seconds,
+            locked: Uint128::zero(),
+            withdrawable: Uint128::zero(),
+            vesting_end: None,
+        }),
+    }
+}
+
+/// Reward streams brought up to the query time
+fn query_reward_streams(deps: Deps, env: Env) -> StdResult<Vec<RewardStream>> {
+    let state = STATE.load(deps.storage)?;
+    let voter_weight = VOTER_REWARD_POOL.may_load(deps.storage)?.unwrap_or_default().total_weight;
+    let voter_bps = voter_reward_bps(deps.storage);
+    let mut streams = reward_streams(deps.storage)?;
+    for stream in streams.iter_mut() {
+        update_stream_index(stream, state.total_allocations, voter_weight, voter_bps, env.block.time);
+    }
+    Ok(streams)
+}
+
+fn query_voter_token_rewards(deps: Deps, env: Env, address: String) -> StdResult<Vec<TokenRewardResponse>> {
+    let address = deps.api.addr_validate(&address)?;
+    let state = STATE.load(deps.storage)?;
+
+    Ok(query_reward_streams(deps, env)?
+        .iter()
+        .map(|stream| TokenRewardResponse {
+            token: stream.token.clone(),
+            pending: pending_voter_token_rewards(deps.storage, &address, state.epoch, stream),
+        })
+        .collect())
+}
+
+fn query_allocation_token_rewards(deps: Deps, env: Env, allocation_id: u32) -> StdResult<Vec<TokenRewardResponse>> {
+    let allocation = ALLOCATION_OPTIONS.get(deps.storage, &allocation_id)
+        .ok_or_else(|| StdError::generic_err("Allocation not found"))?;
+
+    Ok(query_reward_streams(deps, env)?
+        .iter()
+        .map(|stream| TokenRewardResponse {
+            token: stream.token.clone(),
+            pending: pending_token_rewards(deps.storage, allocation_id, allocation.state.amount_allocated, stream),
+        })
+        .collect())
+}
+
+/// Voter rewards including what has accrued since the pool was last updated
+fn query_voter_rewards(deps: Deps, env: Env, address: String) -> StdResult<VoterRewardResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let state = STATE.load(deps.storage)?;
+    let mut pool = VOTER_REWARD_POOL.may_load(deps.storage)?.unwrap_or_default();
+
+    let time_elapsed = env.block.time.seconds().saturating_sub(state.last_upkeep.seconds());
+    if time_elapsed > 0 && !pool.total_weight.is_zero() {
+        let new_rewards = (Uint128::from(time_elapsed) * Uint128::from(REWARD_RATE))
+            .multiply_ratio(voter_reward_bps(deps.storage) as u128, 10_000u128);
+        pool.reward_index += new_rewards.multiply_ratio(INDEX_PRECISION, pool.total_weight);
+    }
+
+    let voter = pending_voter_rewards(deps.storage, &pool, state.epoch, &address);
+    Ok(VoterRewardResponse {
+        epoch: voter.epoch,
+        weight: voter.weight,
+        pending: voter.accumulated_rewards,
+    })
+}
+
+/// Manager and claimer handovers awaiting acceptance, and payout changes awaiting their delay
+fn query_pending_allocation_changes(deps: Deps, allocation_id: u32) -> StdResult<PendingAllocationChangesResponse> {
+    if !ALLOCATION_OPTIONS.contains(deps.storage, &allocation_id) {
+        return Err(StdError::generic_err("Allocation not found"));
+    }
+    Ok(PendingAllocationChangesResponse {
+        pending_manager: PENDING_ALLOCATION_MANAGERS.get(deps.storage, &allocation_id),
+        pending_claimer: PENDING_ALLOCATION_CLAIMERS.get(deps.storage, &allocation_id),
+        pending_receive: PENDING_RECEIVE_CHANGES.get(deps.storage, &allocation_id),
+        pending_recipients: PENDING_RECIPIENTS_CHANGES.get(deps.storage, &allocation_id),
+        pending_claimer_removal: PENDING_CLAIMER_REMOVALS.get(deps.storage, &allocation_id),
+    })
+}
+
+/// Queued admin actions in ascending id order, executed or cancelled ones excluded
+fn query_pending_admin_actions(deps: Deps, start_after: Option<u32>, limit: Option<u32>) -> StdResult<Vec<QueuedAdminAction>> {
+    let counter = ADMIN_ACTION_COUNTER.may_load(deps.storage)?.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.unwrap_or(0) + 1;
+
+    Ok((start..=counter)
+        .filter_map(|id| ADMIN_ACTIONS.get(deps.storage, &id))
+        .take(limit)
+        .collect())
+}
+
+fn query_roles(deps: Deps, address: String) -> StdResult<RolesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let granted = ROLES.get(deps.storage, &address).unwrap_or_default();
+
+    let mut effective = Vec::new();
+    for role in [Role::Admin, Role::Registrar, Role::AllocationAdmin, Role::Pauser, Role::Keeper] {
+        if has_role(deps.storage, &address, role)? {
+            effective.push(role);
+        }
+    }
+
+    Ok(RolesResponse { granted, effective })
+}
+
+fn query_registry_cache(deps: Deps, env: Env) -> StdResult<Vec<RegistryCacheEntry>> {
+    Ok(REGISTRY_CONTRACT_NAMES.iter().map(|name| {
+        let key = name.to_string();
+        let cached = REGISTRY_CACHE.get(deps.storage, &key);
+        let stale = cached.as_ref().is_some_and(|cached| {
+            env.block.time.seconds().saturating_sub(cached.fetched_at.seconds()) >= REGISTRY_CACHE_TTL_SECONDS
+        });
+        RegistryCacheEntry {
+            name: key.clone(),
+            cached,
+            stale,
+            pinned: REGISTRY_PINS.get(deps.storage, &key),
+        }
+    }).collect())
+}
@@ -0,0 +1,22 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::Timestamp;
+use secret_toolkit_storage::{Keymap, Item};
+use crate::msg::AdminAction;
+
+/// A sensitive manager action waiting out the admin timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct QueuedAdminAction {
+    pub id: u32,
+    pub action: AdminAction,
+    pub queued_at: Timestamp,
+    /// Earliest time the action can be executed
+    pub eta: Timestamp,
+}
+
+pub const MAX_ADMIN_TIMELOCK_SECONDS: u64 = 30 * 86400;
+
+// Missing or zero means manager actions apply immediately
+pub static ADMIN_TIMELOCK_SECONDS: Item<u64> = Item::new(b"admin_timelock_seconds");
+keymap!(pub ADMIN_ACTIONS: Keymap<u32, QueuedAdminAction> = Keymap::new(b"admin_actions"));
+pub static ADMIN_ACTION_COUNTER: Item<u32> = Item::new(b"admin_action_counter");
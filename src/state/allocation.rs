@@ -52,6 +52,160 @@ impl Default for UserAllocations {
     }
 }
 
-pub static ALLOCATION_OPTIONS: Keymap<u32, Allocation> = Keymap::new(b"allocation_options_v2");
+/// How much weight each registered user's `SetAllocation` carries
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingWeightMode {
+    /// Every registered user votes with `FLAT_VOTE_WEIGHT`
+    #[default]
+    Flat,
+    /// Weight grows with the age of the user's registration, up to twice the flat weight
+    Seniority,
+    /// Weight follows the user's ANML balance snapshot, `FLAT_VOTE_WEIGHT` per whole ANML
+    AnmlBalance,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VotingConfig {
+    pub weight_mode: VotingWeightMode,
+}
+
+/// How a user's weighted percentages are turned into `amount_allocated`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TallyMode {
+    /// Each allocation receives the user's weighted percentage as-is
+    #[default]
+    Linear,
+    /// Each allocation receives the square root of the user's weighted percentage (the credits)
+    Quadratic,
+}
+
+/// Units `AllocationPercentage.percentage` is expressed in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PercentageScale {
+    /// Whole percent, allocations sum to 100
+    #[default]
+    Percent,
+    /// Basis points, allocations sum to 10,000
+    BasisPoints,
+}
+
+impl PercentageScale {
+    /// What a user's allocations must sum to
+    pub fn total(&self) -> Uint128 {
+        match self {
+            PercentageScale::Percent => Uint128::from(100u32),
+            PercentageScale::BasisPoints => Uint128::from(10_000u32),
+        }
+    }
+}
+
+/// Voting rules fixed for the lifetime of an epoch. Because an epoch's votes are always
+/// tallied under a single scale, switching scales only takes effect from an epoch boundary
+/// and stored `USER_ALLOCATIONS` are read with the scale of the epoch they were cast in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct EpochVotingRules {
+    pub tally_mode: TallyMode,
+    pub percentage_scale: PercentageScale,
+}
+
+/// Weight a user's allocations were applied with, so they can be removed exactly,
+/// and the registration expiry after which they stop counting
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UserVote {
+    pub epoch: u32,
+    pub weight: Uint128,
+    pub expires: Timestamp,
+}
+
+pub const FLAT_VOTE_WEIGHT: u128 = 100;
+
+/// A user's choice to have their last allocations carried into new epochs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AutoRenew {
+    /// An epoch the user opted out of carrying their allocations into
+    pub skip_epoch: Option<u32>,
+}
+
+/// Next page of `AUTO_RENEW` the keeper sweep processes for an epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AutoRenewCursor {
+    pub epoch: u32,
+    pub page: u32,
+}
+
+pub const AUTO_RENEW_PAGE_SIZE: u32 = 20;
+
+/// Expiry days still to be re-checked after registration validity was shortened
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VoteExpiryRescan {
+    pub next_day: u64,
+    /// Position within `next_day`'s bucket
+    pub next_index: u32,
+    pub last_day: u64,
+}
+
+/// Running tallies of an allocation for the epoch in progress
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct AllocationEpochStats {
+    pub epoch: u32,
+    pub voters: u32,
+    pub rewards_earned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EpochAllocationResult {
+    pub allocation_id: u32,
+    pub amount_allocated: Uint128,
+    pub voters: u32,
+    pub rewards_earned: Uint128,
+}
+
+/// Closing tallies of an epoch, written once by `reset_allocations`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EpochResults {
+    pub epoch: u32,
+    pub closed_at: Timestamp,
+    pub total_allocations: Uint128,
+    pub rules: EpochVotingRules,
+    pub allocations: Vec<EpochAllocationResult>,
+}
+
+/// One payee of a split allocation; rewards are divided in proportion to `weight`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AllocationRecipient {
+    pub addr: Addr,
+    pub code_hash: Option<String>,
+    pub use_send: bool,
+    pub weight: u32,
+}
+
+pub const MAX_ALLOCATION_RECIPIENTS: usize = 10;
+
+keymap!(pub ALLOCATION_OPTIONS: Keymap<u32, Allocation> = Keymap::new(b"allocation_options_v2"));
 pub static ALLOCATION_IDS: Item<Vec<u32>> = Item::new(b"allocation_ids");
-pub static USER_ALLOCATIONS: Keymap<Addr, UserAllocations> = Keymap::new(b"user_allocations_v0.0.2");
+keymap!(pub USER_ALLOCATIONS: Keymap<Addr, UserAllocations> = Keymap::new(b"user_allocations_v0.0.2"));
+pub static VOTING_CONFIG: Item<VotingConfig> = Item::new(b"voting_config");
+// Epochs without an entry use the default (linear) rules
+keymap!(pub EPOCH_VOTING_RULES: Keymap<u32, EpochVotingRules> = Keymap::new(b"epoch_voting_rules"));
+// Kept apart from USER_ALLOCATIONS so existing entries stay readable; missing means flat weight
+keymap!(pub USER_VOTES: Keymap<Addr, UserVote> = Keymap::new(b"user_votes"));
+// Only users who enabled auto-renew have an entry
+keymap!(pub AUTO_RENEW: Keymap<Addr, AutoRenew> = Keymap::new(b"auto_renew"));
+pub static AUTO_RENEW_CURSOR: Item<AutoRenewCursor> = Item::new(b"auto_renew_cursor");
+keymap!(pub ALLOCATION_EPOCH_STATS: Keymap<u32, AllocationEpochStats> = Keymap::new(b"allocation_epoch_stats"));
+keymap!(pub EPOCH_RESULTS: Keymap<u32, EpochResults> = Keymap::new(b"epoch_results"));
+// Voters bucketed by the day (seconds / 86400) their vote expires, drained in day order
+keymap!(pub VOTE_EXPIRY_QUEUE: Keymap<u64, Vec<Addr>> = Keymap::new(b"vote_expiry_queue"));
+pub static VOTE_EXPIRY_CURSOR: Item<u64> = Item::new(b"vote_expiry_cursor");
+// Present while a shortened registration validity is being applied to queued votes
+pub static VOTE_EXPIRY_RESCAN: Item<VoteExpiryRescan> = Item::new(b"vote_expiry_rescan");
+// Present while votes cast before the expiry queue existed are being queued; the next
+// `USER_ALLOCATIONS` position to look at
+pub static VOTE_EXPIRY_BACKFILL: Item<u32> = Item::new(b"vote_expiry_backfill");
+// Allocations without an entry pay their `receive_addr` in full
+keymap!(pub ALLOCATION_RECIPIENTS: Keymap<u32, Vec<AllocationRecipient>> = Keymap::new(b"allocation_recipients"));
+// ERTH sent to an allocation by third parties, held until the allocation's next claim
+keymap!(pub ALLOCATION_BONUS_POOL: Keymap<u32, Uint128> = Keymap::new(b"allocation_bonus_pool"));
@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Timestamp};
+use secret_toolkit_storage::Keymap;
+use crate::state::AllocationRecipient;
+
+/// Payout destination change waiting out `PAYOUT_CHANGE_DELAY_SECONDS`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingReceiveChange {
+    pub receive_addr: Addr,
+    pub receive_hash: Option<String>,
+    pub use_send: bool,
+    pub effective_at: Timestamp,
+}
+
+/// Split recipient change waiting out `PAYOUT_CHANGE_DELAY_SECONDS`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingRecipientsChange {
+    pub recipients: Vec<AllocationRecipient>,
+    pub effective_at: Timestamp,
+}
+
+/// Gives recipients time to notice a hijacked manager before payouts are redirected
+pub const PAYOUT_CHANGE_DELAY_SECONDS: u64 = 3 * 86400;
+
+// Proposed managers and claimers, set once the proposed address accepts
+keymap!(pub PENDING_ALLOCATION_MANAGERS: Keymap<u32, Addr> = Keymap::new(b"pending_allocation_managers"));
+keymap!(pub PENDING_ALLOCATION_CLAIMERS: Keymap<u32, Addr> = Keymap::new(b"pending_allocation_claimers"));
+keymap!(pub PENDING_RECEIVE_CHANGES: Keymap<u32, PendingReceiveChange> = Keymap::new(b"pending_receive_changes"));
+keymap!(pub PENDING_RECIPIENTS_CHANGES: Keymap<u32, PendingRecipientsChange> = Keymap::new(b"pending_recipients_changes"));
+// When a claimer removal takes effect; until then the current claimer keeps claiming
+keymap!(pub PENDING_CLAIMER_REMOVALS: Keymap<u32, Timestamp> = Keymap::new(b"pending_claimer_removals"));
@@ -33,7 +33,7 @@ pub enum RegistryQueryMsg {
     GetContracts { names: Vec<String> },
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ContractInfo {
     pub address: Addr,
     pub code_hash: String,
@@ -74,5 +74,41 @@ pub fn query_registry(
     Ok(response.contracts.into_iter().map(|c| c.info).collect())
 }
 
+// Minimal ANML token types for voting weight snapshots
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnmlQueryMsg {
+    BalanceSnapshot { address: String },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BalanceSnapshotResponse {
+    pub amount: Uint128,
+}
+
+/// Query the ANML token for an address's balance snapshot
+pub fn query_anml_balance(
+    deps: &Deps,
+    anml_token: &ContractInfo,
+    address: &Addr,
+) -> StdResult<Uint128> {
+    let response: BalanceSnapshotResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: anml_token.address.to_string(),
+        code_hash: anml_token.code_hash.clone(),
+        msg: to_binary(&AnmlQueryMsg::BalanceSnapshot { address: address.to_string() })?,
+    }))?;
+    Ok(response.amount)
+}
+
+/// Proposed contract manager awaiting acceptance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingManager {
+    pub address: Addr,
+    pub expires: Timestamp,
+}
+
+pub const MANAGER_PROPOSAL_TTL_SECONDS: u64 = 7 * 86400;
+
 pub static CONFIG: Item<Config> = Item::new(b"config");
+pub static PENDING_MANAGER: Item<PendingManager> = Item::new(b"pending_manager");
 pub static STATE: Item<State> = Item::new(b"state");
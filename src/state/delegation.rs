@@ -0,0 +1,22 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use secret_toolkit_storage::Keymap;
+
+/// A registered user following another user's allocations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Delegation {
+    pub delegate: Addr,
+    /// The delegator's own voting weight when it was last applied upstream
+    pub weight: Uint128,
+    /// Registration expiry of the delegator, after which the delegation lapses
+    pub expires: Timestamp,
+}
+
+pub const MAX_DELEGATION_DEPTH: usize = 8;
+
+keymap!(pub DELEGATIONS: Keymap<Addr, Delegation> = Keymap::new(b"delegations"));
+// Total weight flowing into an address from its direct and indirect delegators
+keymap!(pub DELEGATED_WEIGHT: Keymap<Addr, Uint128> = Keymap::new(b"delegated_weight"));
+// Delegators reaching an address by chain length: `counts[i]` of them are i + 1 hops upstream
+keymap!(pub UPSTREAM_DELEGATORS: Keymap<Addr, Vec<u32>> = Keymap::new(b"upstream_delegators"));
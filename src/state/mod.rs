@@ -1,11 +1,58 @@
 // src/state/mod.rs
 
+// Keymaps cache their length on the instance, so a `static` would carry one test's length
+// into the next test's storage; test builds declare them `const` instead
+macro_rules! keymap {
+    ($vis:vis $name:ident: $ty:ty = $init:expr) => {
+        #[cfg(not(test))]
+        $vis static $name: $ty = $init;
+        #[cfg(test)]
+        $vis const $name: $ty = $init;
+    };
+}
+
 pub mod config;
 pub mod registration;
 pub mod allocation;
+pub mod delegation;
+pub mod proposal;
+pub mod vesting;
+pub mod reward_stream;
+pub mod voter_reward;
+pub mod allocation_changes;
+pub mod admin;
+pub mod roles;
+pub mod pause;
+pub mod registry;
+pub mod version;
 
-pub use config::{Config, CONFIG, State, STATE, ContractInfo, query_registry};
+pub use config::{Config, CONFIG, State, STATE, ContractInfo, query_registry, query_anml_balance,
+    PendingManager, PENDING_MANAGER, MANAGER_PROPOSAL_TTL_SECONDS};
 pub use registration::{REGISTRATIONS, Registration, NEW_REGISTRATIONS_COUNT};
 pub use allocation::{Allocation, AllocationConfig, AllocationPercentage, AllocationState,
-    UserAllocations, USER_ALLOCATIONS, ALLOCATION_OPTIONS, ALLOCATION_IDS, MAX_DESCRIPTION_LENGTH};
+    UserAllocations, USER_ALLOCATIONS, ALLOCATION_OPTIONS, ALLOCATION_IDS, MAX_DESCRIPTION_LENGTH,
+    VotingWeightMode, VotingConfig, VOTING_CONFIG, UserVote, USER_VOTES, FLAT_VOTE_WEIGHT,
+    TallyMode, PercentageScale, EpochVotingRules, EPOCH_VOTING_RULES, VOTE_EXPIRY_QUEUE, VOTE_EXPIRY_CURSOR,
+    VoteExpiryRescan, VOTE_EXPIRY_RESCAN, VOTE_EXPIRY_BACKFILL,
+    AllocationEpochStats, ALLOCATION_EPOCH_STATS, EpochAllocationResult, EpochResults, EPOCH_RESULTS,
+    AutoRenew, AutoRenewCursor, AUTO_RENEW, AUTO_RENEW_CURSOR, AUTO_RENEW_PAGE_SIZE,
+    AllocationRecipient, ALLOCATION_RECIPIENTS, MAX_ALLOCATION_RECIPIENTS,
+    ALLOCATION_BONUS_POOL};
+pub use delegation::{Delegation, DELEGATIONS, DELEGATED_WEIGHT, UPSTREAM_DELEGATORS, MAX_DELEGATION_DEPTH};
+pub use proposal::{ProposalStatus, AllocationProposal, ProposalConfig, PROPOSAL_CONFIG, PROPOSALS, PROPOSAL_COUNTER,
+    PROPOSAL_ENDORSEMENTS, MIN_ENDORSEMENT_THRESHOLD};
+pub use vesting::{VestingSchedule, VestingTranche, MAX_VESTING_TRANCHES, ALLOCATION_VESTING_DURATION, ALLOCATION_VESTING};
+pub use reward_stream::{RewardStream, AllocationTokenReward, VoterTokenReward, MAX_REWARD_TOKENS, REWARD_TOKENS,
+    REWARD_STREAMS, ALLOCATION_TOKEN_REWARDS, VOTER_TOKEN_REWARDS, EPOCH_VOTER_TOKEN_INDEX};
+pub use voter_reward::{VoterRewardConfig, VoterRewardPool, VoterReward, MAX_VOTER_REWARD_BPS, VOTER_REWARD_CONFIG,
+    VOTER_REWARD_POOL, VOTER_REWARDS, EPOCH_VOTER_INDEX};
+pub use allocation_changes::{PendingReceiveChange, PendingRecipientsChange, PAYOUT_CHANGE_DELAY_SECONDS,
+    PENDING_ALLOCATION_MANAGERS, PENDING_ALLOCATION_CLAIMERS, PENDING_RECEIVE_CHANGES, PENDING_RECIPIENTS_CHANGES,
+    PENDING_CLAIMER_REMOVALS};
+pub use admin::{QueuedAdminAction, MAX_ADMIN_TIMELOCK_SECONDS, ADMIN_TIMELOCK_SECONDS, ADMIN_ACTIONS,
+    ADMIN_ACTION_COUNTER};
+pub use roles::{Role, ROLES};
+pub use pause::{PauseScope, PauseStatus, PAUSE_STATUS};
+pub use version::{ContractVersion, CONTRACT_NAME, CONTRACT_VERSION, STORED_VERSION};
+pub use registry::{CachedContract, REGISTRY_CONTRACT_NAMES, REGISTRY_CACHE_TTL_SECONDS, REGISTRY_CACHE, REGISTRY_PINS};
 pub use crate::msg::{RegistrationStatusResponse};
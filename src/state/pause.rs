@@ -0,0 +1,49 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use secret_toolkit_storage::Item;
+
+/// Independently pausable parts of the contract
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    Registration,
+    AnmlClaims,
+    Buybacks,
+    AllocationVoting,
+    AllocationClaims,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct PauseStatus {
+    pub registration: bool,
+    pub anml_claims: bool,
+    /// ANML claims still go through but skip the ERTH -> ANML buyback swap
+    pub buybacks: bool,
+    pub allocation_voting: bool,
+    pub allocation_claims: bool,
+}
+
+impl PauseStatus {
+    pub fn is_paused(&self, scope: PauseScope) -> bool {
+        match scope {
+            PauseScope::Registration => self.registration,
+            PauseScope::AnmlClaims => self.anml_claims,
+            PauseScope::Buybacks => self.buybacks,
+            PauseScope::AllocationVoting => self.allocation_voting,
+            PauseScope::AllocationClaims => self.allocation_claims,
+        }
+    }
+
+    pub fn set(&mut self, scope: PauseScope, paused: bool) {
+        match scope {
+            PauseScope::Registration => self.registration = paused,
+            PauseScope::AnmlClaims => self.anml_claims = paused,
+            PauseScope::Buybacks => self.buybacks = paused,
+            PauseScope::AllocationVoting => self.allocation_voting = paused,
+            PauseScope::AllocationClaims => self.allocation_claims = paused,
+        }
+    }
+}
+
+// Missing means nothing is paused
+pub static PAUSE_STATUS: Item<PauseStatus> = Item::new(b"pause_status");
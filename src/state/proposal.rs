@@ -0,0 +1,43 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use secret_toolkit_storage::{Keymap, Item};
+use crate::state::AllocationConfig;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Pending,
+    Approved { allocation_id: u32 },
+    Rejected { slashed: bool },
+}
+
+/// A community-submitted allocation option awaiting approval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AllocationProposal {
+    pub proposal_id: u32,
+    pub proposer: Addr,
+    pub deposit: Uint128,
+    pub config: AllocationConfig,
+    pub endorsements: u32,
+    pub status: ProposalStatus,
+    pub submitted: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ProposalConfig {
+    /// Minimum ERTH deposit sent with a proposal
+    pub min_deposit: Uint128,
+    /// Registered-human endorsements that approve a proposal; 0 leaves approval to the manager
+    pub endorsement_threshold: u32,
+}
+
+/// Lowest non-zero `endorsement_threshold`
+pub const MIN_ENDORSEMENT_THRESHOLD: u32 = 10;
+
+// Proposals are disabled until the contract manager sets a config
+pub static PROPOSAL_CONFIG: Item<ProposalConfig> = Item::new(b"proposal_config");
+keymap!(pub PROPOSALS: Keymap<u32, AllocationProposal> = Keymap::new(b"allocation_proposals"));
+pub static PROPOSAL_COUNTER: Item<u32> = Item::new(b"proposal_counter");
+// Suffixed with the proposal id; tracks who already endorsed it
+keymap!(pub PROPOSAL_ENDORSEMENTS: Keymap<Addr, bool> = Keymap::new(b"proposal_endorsements"));
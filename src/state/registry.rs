@@ -0,0 +1,22 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::Timestamp;
+use secret_toolkit_storage::Keymap;
+use crate::state::ContractInfo;
+
+/// A registry lookup kept in storage so executions don't query the registry every time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CachedContract {
+    pub info: ContractInfo,
+    pub fetched_at: Timestamp,
+}
+
+/// Registry names the contract resolves through the cache
+pub const REGISTRY_CONTRACT_NAMES: [&str; 3] = ["erth_token", "anml_token", "exchange"];
+
+/// Entries older than this are re-queried; stale entries are still used if the registry fails
+pub const REGISTRY_CACHE_TTL_SECONDS: u64 = 86400;
+
+keymap!(pub REGISTRY_CACHE: Keymap<String, CachedContract> = Keymap::new(b"registry_cache"));
+// Manager overrides that take precedence over the registry until unpinned
+keymap!(pub REGISTRY_PINS: Keymap<String, ContractInfo> = Keymap::new(b"registry_pins"));
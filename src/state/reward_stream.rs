@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use secret_toolkit_storage::{Keymap, Item};
+
+/// A partner SNIP-20 token streamed to allocations and their voters alongside ERTH emissions.
+/// Voters receive the same share of each stream as of the ERTH emission.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RewardStream {
+    pub token: Addr,
+    pub code_hash: String,
+    /// Rewards per allocated unit, scaled like `State::reward_index`
+    pub reward_index: Uint128,
+    /// Rewards per unit of vote weight, scaled like `VoterRewardPool::reward_index`
+    pub voter_reward_index: Uint128,
+    /// Deposited tokens not streamed yet
+    pub remaining: Uint128,
+    pub stream_end: Timestamp,
+    pub last_update: Timestamp,
+}
+
+/// An allocation's position in one reward stream
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct AllocationTokenReward {
+    pub last_reward_index: Uint128,
+    pub accumulated_rewards: Uint128,
+}
+
+/// A voter's position in one reward stream; the weight earning is the voter's `VoterReward`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VoterTokenReward {
+    pub last_reward_index: Uint128,
+    pub accumulated_rewards: Uint128,
+}
+
+/// Every stream is settled on each vote change, so the list is kept short
+pub const MAX_REWARD_TOKENS: usize = 5;
+
+pub static REWARD_TOKENS: Item<Vec<Addr>> = Item::new(b"reward_tokens");
+keymap!(pub REWARD_STREAMS: Keymap<Addr, RewardStream> = Keymap::new(b"reward_streams"));
+// Keyed by token, suffixed with the allocation id
+keymap!(pub ALLOCATION_TOKEN_REWARDS: Keymap<Addr, AllocationTokenReward> = Keymap::new(b"allocation_token_rewards"));
+// Keyed by token, suffixed with the voter address
+keymap!(pub VOTER_TOKEN_REWARDS: Keymap<Addr, VoterTokenReward> = Keymap::new(b"voter_token_rewards"));
+// Keyed by epoch, suffixed with the token: voter index of the stream when the epoch was reset
+keymap!(pub EPOCH_VOTER_TOKEN_INDEX: Keymap<u32, Uint128> = Keymap::new(b"epoch_voter_token_index"));
@@ -0,0 +1,23 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::Addr;
+use secret_toolkit_storage::Keymap;
+
+/// Permissions that can be granted on top of the contract manager, who implicitly holds every role
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Contract configuration, timelock and role management; implies every other role
+    Admin,
+    /// Registering users (the configured `registration_address` always has it)
+    Registrar,
+    /// Adding, editing and resetting allocations and deciding proposals
+    AllocationAdmin,
+    /// Pausing and unpausing the contract
+    Pauser,
+    /// Running maintenance entry points such as expiry and auto-renew processing
+    Keeper,
+}
+
+// Explicitly granted roles; implicit roles of the manager and registration address aren't stored
+keymap!(pub ROLES: Keymap<Addr, Vec<Role>> = Keymap::new(b"roles"));
@@ -0,0 +1,40 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Timestamp, Uint128};
+use secret_toolkit_storage::Keymap;
+
+/// Part of one claim that is still locked
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingTranche {
+    /// Amount releasing linearly between `start` and `end`
+    pub locked: Uint128,
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+/// Claimed ERTH held by the contract for an allocation. Each claim vests in its own tranche
+/// running `duration` from the claim, so later claims never delay amounts already locked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VestingSchedule {
+    pub tranches: Vec<VestingTranche>,
+    /// Released but not yet withdrawn
+    pub withdrawable: Uint128,
+}
+
+impl VestingSchedule {
+    pub fn locked(&self) -> Uint128 {
+        self.tranches.iter().map(|tranche| tranche.locked).sum()
+    }
+
+    /// When the last locked tranche finishes vesting
+    pub fn end(&self) -> Option<Timestamp> {
+        self.tranches.iter().map(|tranche| tranche.end).max()
+    }
+}
+
+// Beyond this, a claim is merged into the newest tranche
+pub const MAX_VESTING_TRANCHES: usize = 30;
+
+// Allocations without an entry pay out on claim
+keymap!(pub ALLOCATION_VESTING_DURATION: Keymap<u32, u64> = Keymap::new(b"allocation_vesting_duration"));
+keymap!(pub ALLOCATION_VESTING: Keymap<u32, VestingSchedule> = Keymap::new(b"allocation_vesting"));
@@ -0,0 +1,37 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Uint128};
+use secret_toolkit_storage::{Keymap, Item};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VoterRewardConfig {
+    /// Share of the per-second ERTH emission paid to voters, in basis points
+    pub voter_reward_bps: u16,
+}
+
+/// Voter side of the emission for the current epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VoterRewardPool {
+    /// Rewards per unit of vote weight, scaled like `State::reward_index`
+    pub reward_index: Uint128,
+    /// Weight of every vote tallied in the current epoch
+    pub total_weight: Uint128,
+}
+
+/// A user's position in the voter reward pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct VoterReward {
+    /// Epoch `weight` was voted in; it stops earning once that epoch is reset
+    pub epoch: u32,
+    pub weight: Uint128,
+    pub last_reward_index: Uint128,
+    pub accumulated_rewards: Uint128,
+}
+
+pub const MAX_VOTER_REWARD_BPS: u16 = 5_000;
+
+pub static VOTER_REWARD_CONFIG: Item<VoterRewardConfig> = Item::new(b"voter_reward_config");
+pub static VOTER_REWARD_POOL: Item<VoterRewardPool> = Item::new(b"voter_reward_pool");
+keymap!(pub VOTER_REWARDS: Keymap<Addr, VoterReward> = Keymap::new(b"voter_rewards"));
+// Voter reward index at the moment each epoch was reset
+keymap!(pub EPOCH_VOTER_INDEX: Keymap<u32, Uint128> = Keymap::new(b"epoch_voter_index"));
@@ -0,0 +1,100 @@
+// src/testing.rs
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{from_binary, to_binary, Addr, ContractResult, Env, OwnedDeps, Response, SystemResult, Uint128, WasmQuery};
+use crate::msg::{ExecuteMsg, InstantiateMsg, ReceiveMsg};
+use crate::state::config::{AllContractsResponse, ContractResponse, RegistryQueryMsg};
+use crate::state::{AllocationConfig, AllocationPercentage, ContractInfo};
+use crate::error::ContractError;
+use crate::{execute, instantiate};
+
+pub type TestDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+pub const MANAGER: &str = "manager";
+pub const REGISTRAR: &str = "registrar";
+/// Address the mocked registry resolves `erth_token` to
+pub const ERTH_TOKEN: &str = "erth_token";
+
+/// Instantiated contract whose registry resolves every name to a contract of that name
+pub fn setup() -> TestDeps {
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let RegistryQueryMsg::GetContracts { names } = from_binary(msg).unwrap();
+            let contracts = names.into_iter()
+                .map(|name| ContractResponse {
+                    info: ContractInfo { address: Addr::unchecked(&name), code_hash: format!("{}_hash", name) },
+                    name,
+                })
+                .collect();
+            SystemResult::Ok(ContractResult::Ok(to_binary(&AllContractsResponse { contracts }).unwrap()))
+        }
+        _ => panic!("unexpected query {:?}", query),
+    });
+    instantiate(deps.as_mut(), mock_env(), mock_info(MANAGER, &[]), InstantiateMsg {
+        registration_address: REGISTRAR.to_string(),
+        registration_wallet: "wallet".to_string(),
+        contract_manager: MANAGER.to_string(),
+        registry_contract: "registry".to_string(),
+        registry_hash: "0".repeat(64),
+    }).unwrap();
+    deps
+}
+
+pub fn exec(deps: &mut TestDeps, env: Env, sender: &str, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+}
+
+pub fn register(deps: &mut TestDeps, env: Env, address: &str) -> Response {
+    exec(deps, env, REGISTRAR, ExecuteMsg::Register {
+        address: address.to_string(),
+        id_hash: format!("{}_id", address),
+        affiliate: None,
+    }).unwrap()
+}
+
+/// Deliver an ERTH `Send` from `from` (use `MOCK_CONTRACT_ADDR` for payouts to this contract)
+pub fn receive_erth(
+    deps: &mut TestDeps,
+    env: Env,
+    from: &str,
+    amount: u128,
+    msg: &ReceiveMsg,
+) -> Result<Response, ContractError> {
+    exec(deps, env, ERTH_TOKEN, ExecuteMsg::Receive {
+        sender: Addr::unchecked(from),
+        from: Addr::unchecked(from),
+        amount: Uint128::new(amount),
+        memo: None,
+        msg: to_binary(msg).unwrap(),
+    })
+}
+
+/// Add an allocation paying out to `receive_addr` by plain transfer, returning its id
+pub fn add_allocation(deps: &mut TestDeps, receive_addr: &str) -> u32 {
+    let response = exec(deps, mock_env(), MANAGER, ExecuteMsg::AddAllocation {
+        config: AllocationConfig {
+            description: String::new(),
+            receive_addr: Addr::unchecked(receive_addr),
+            receive_hash: None,
+            manager_addr: None,
+            claimer_addr: None,
+            use_send: false,
+        },
+    }).unwrap();
+    response.attributes.iter()
+        .find(|attr| attr.key == "allocation_id")
+        .map(|attr| attr.value.parse().unwrap())
+        .unwrap()
+}
+
+/// Vote `percentages` as (allocation id, whole percent) pairs
+pub fn vote(deps: &mut TestDeps, env: Env, voter: &str, percentages: &[(u32, u128)]) -> Result<Response, ContractError> {
+    exec(deps, env, voter, ExecuteMsg::SetAllocation {
+        percentages: percentages.iter()
+            .map(|(allocation_id, percentage)| AllocationPercentage {
+                allocation_id: *allocation_id,
+                percentage: Uint128::new(*percentage),
+            })
+            .collect(),
+    })
+}